@@ -4,38 +4,15 @@ use std::{cell::RefCell, rc::Rc};
 
 #[derive(Default, Debug, Clone)]
 struct CalculatorNode {
-    inputs: [Port<f64, ()>; 2],
-    outputs: [Port<(), f64>; 5],
+    inputs: [Port<f64, ()>; CalculatorNode::NUM_INPUTS],
+    outputs: [Port<(), f64>; CalculatorNode::NUM_OUTPUTS],
     multiplier: f64,
 }
 
 impl CalculatorNode {
-    pub const fn input_index_lhs() -> PortIndex {
-        PortIndex::new(0)
-    }
-
-    pub const fn input_index_rhs() -> PortIndex {
-        PortIndex::new(1)
-    }
-
-    pub const fn output_index_lhs_neg() -> PortIndex {
-        PortIndex::new(0)
-    }
-
-    pub const fn output_index_rhs_neg() -> PortIndex {
-        PortIndex::new(1)
-    }
-
-    pub const fn output_index_sum() -> PortIndex {
-        PortIndex::new(2)
-    }
-
-    pub const fn output_index_diff() -> PortIndex {
-        PortIndex::new(3)
-    }
-
-    pub const fn output_index_prod() -> PortIndex {
-        PortIndex::new(4)
+    flowcalc::define_ports! {
+        inputs: [lhs, rhs],
+        outputs: [lhs_neg, rhs_neg, sum, diff, prod],
     }
 
     pub fn set_multiplier(&mut self, multiplier: f64) {
@@ -45,24 +22,14 @@ impl CalculatorNode {
         self.multiplier = multiplier;
     }
 
-    pub fn input(&self, input_index: PortIndex) -> &Port<f64, ()> {
-        debug_assert!(input_index < PortIndex::new(self.num_inputs()));
-        &self.inputs[usize::from(input_index)]
-    }
-
     pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<f64, ()> {
-        debug_assert!(input_index < PortIndex::new(self.num_inputs()));
-        &mut self.inputs[usize::from(input_index)]
-    }
-
-    pub fn output(&self, output_index: PortIndex) -> &Port<(), f64> {
-        debug_assert!(output_index < PortIndex::new(self.num_outputs()));
-        &self.outputs[usize::from(output_index)]
+        debug_assert!(input_index < self.num_inputs());
+        &mut self.inputs[input_index.index()]
     }
 
     pub fn output_mut(&mut self, output_index: PortIndex) -> &mut Port<(), f64> {
-        debug_assert!(output_index < PortIndex::new(self.num_outputs()));
-        &mut self.outputs[usize::from(output_index)]
+        debug_assert!(output_index < self.num_outputs());
+        &mut self.outputs[output_index.index()]
     }
 }
 
@@ -70,7 +37,7 @@ impl Node<(), f64> for CalculatorNode {}
 
 impl NodeInputs<(), f64> for CalculatorNode {
     fn num_inputs(&self) -> usize {
-        2
+        Self::NUM_INPUTS
     }
 
     fn accept_input_packet(
@@ -93,7 +60,7 @@ impl NodeInputs<(), f64> for CalculatorNode {
 
 impl NodeOutputs<(), f64> for CalculatorNode {
     fn num_outputs(&self) -> usize {
-        5
+        Self::NUM_OUTPUTS
     }
 
     fn accept_output_packet(
@@ -112,6 +79,10 @@ impl NodeOutputs<(), f64> for CalculatorNode {
     ) -> Option<Packet<f64, ()>> {
         self.output_mut(output_index).try_dispatch_packet()
     }
+
+    fn reset_output_control(&mut self, _token: AccessToken, output_index: PortIndex) {
+        self.output_mut(output_index).incoming = None;
+    }
 }
 
 impl NodeProcessor for CalculatorNode {
@@ -124,23 +95,15 @@ impl NodeProcessor for CalculatorNode {
             }
             let value = match index {
                 0 => {
-                    debug_assert_eq!(index, Self::output_index_lhs_neg().into());
-                    if let Some(value) = lhs_input_value {
-                        Some(-value)
-                    } else {
-                        None
-                    }
+                    debug_assert_eq!(index, Self::output_index_lhs_neg());
+                    lhs_input_value.map(|value| -value)
                 }
                 1 => {
-                    debug_assert_eq!(index, Self::output_index_rhs_neg().into());
-                    if let Some(value) = rhs_input_value {
-                        Some(-value)
-                    } else {
-                        None
-                    }
+                    debug_assert_eq!(index, Self::output_index_rhs_neg());
+                    rhs_input_value.map(|value| -value)
                 }
                 2 => {
-                    debug_assert_eq!(index, Self::output_index_sum().into());
+                    debug_assert_eq!(index, Self::output_index_sum());
                     if let (Some(lhs), Some(rhs)) = (lhs_input_value, rhs_input_value) {
                         Some(lhs + rhs)
                     } else {
@@ -148,7 +111,7 @@ impl NodeProcessor for CalculatorNode {
                     }
                 }
                 3 => {
-                    debug_assert_eq!(index, Self::output_index_diff().into());
+                    debug_assert_eq!(index, Self::output_index_diff());
                     if let (Some(lhs), Some(rhs)) = (lhs_input_value, rhs_input_value) {
                         Some(lhs - rhs)
                     } else {
@@ -156,7 +119,7 @@ impl NodeProcessor for CalculatorNode {
                     }
                 }
                 4 => {
-                    debug_assert_eq!(index, Self::output_index_prod().into());
+                    debug_assert_eq!(index, Self::output_index_prod());
                     if let (Some(lhs), Some(rhs)) = (lhs_input_value, rhs_input_value) {
                         Some(lhs * rhs)
                     } else {
@@ -172,14 +135,18 @@ impl NodeProcessor for CalculatorNode {
 
     fn process_outputs(&mut self, _: AccessToken) {
         // Needed for all outputs except the negation of the rhs input
-        let lhs_active = self.outputs.iter().enumerate().any(|(i, output)| {
-            i != Self::output_index_rhs_neg().into() && output.incoming.is_some()
-        });
+        let lhs_active = self
+            .outputs
+            .iter()
+            .enumerate()
+            .any(|(i, output)| i != Self::output_index_rhs_neg() && output.incoming.is_some());
         self.input_mut(Self::input_index_lhs()).outgoing = if lhs_active { Some(()) } else { None };
         // Needed for all outputs except the negation of the lhs input
-        let rhs_active = self.outputs.iter().enumerate().any(|(i, output)| {
-            i != Self::output_index_lhs_neg().into() && output.incoming.is_some()
-        });
+        let rhs_active = self
+            .outputs
+            .iter()
+            .enumerate()
+            .any(|(i, output)| i != Self::output_index_lhs_neg() && output.incoming.is_some());
         self.input_mut(Self::input_index_rhs()).outgoing = if rhs_active { Some(()) } else { None };
     }
 }
@@ -203,12 +170,10 @@ fn main() {
     // Print only selected outputs from the calculator
     printer
         .borrow_mut()
-        .input_mut(CalculatorNode::output_index_sum())
-        .outgoing = Some(());
+        .request_input(CalculatorNode::output_index_sum(), ());
     printer
         .borrow_mut()
-        .input_mut(CalculatorNode::output_index_prod())
-        .outgoing = Some(());
+        .request_input(CalculatorNode::output_index_prod(), ());
 
     let mut flow: Flow<RcProxyNode<(), f64>, (), f64> = Flow::new();
     let printer_id = flow.add_node(RcProxyNode::new(Rc::clone(&printer) as _));
@@ -245,21 +210,15 @@ fn main() {
 
     //println!("flow = {:#?}", flow);
 
-    let topo_nodes = flow.topological_nodes().unwrap();
-    //println!("topo_nodes = {:?}", topo_nodes);
-
     for i in 0..10 {
         // Backward pass
-        for node in topo_nodes.iter().rev() {
-            flow.process_outputs(*node);
-        }
+        flow.process_backward_all().unwrap();
 
         // Inject input values...
         {
             let mut splitter_node = splitter.borrow_mut();
-            let single_input = splitter_node.input_mut();
-            if single_input.outgoing.is_some() {
-                single_input.incoming = Some(f64::from(i));
+            if splitter_node.is_input_requested() {
+                splitter_node.input_mut().incoming = Some(f64::from(i));
             }
             // release mutable borrow at runtime
         }
@@ -270,8 +229,6 @@ fn main() {
         }
 
         // Forward pass
-        for node in topo_nodes.iter() {
-            flow.process_inputs(*node);
-        }
+        flow.process_forward_all().unwrap();
     }
 }
@@ -0,0 +1,67 @@
+use flowcalc::{flow::*, node::*, port::*};
+
+flowcalc::node_enum! {
+    enum CalcFlowNode: Node<(), f64> {
+        Splitter(OneToManySplitter<(), f64>),
+        Calc(CalcNode<()>),
+        Printer(DebugPrinterSink<(), f64>),
+    }
+}
+
+fn main() {
+    let calculator = CalcNode::<()>::with_default_operations();
+    let splitter = OneToManySplitter::<(), f64>::new(calculator.num_inputs());
+    let mut printer = DebugPrinterSink::<(), f64>::new(calculator.num_outputs());
+    // Print only selected outputs from the calculator
+    printer.request_input(PortIndex::new(2), ());
+    printer.request_input(PortIndex::new(4), ());
+
+    // All nodes are owned by the flow graph
+    let mut flow: Flow<CalcFlowNode, (), f64> = Flow::new();
+    let printer_id = flow.add_node(printer.into());
+    let splitter_id = flow.add_node(splitter.into());
+    let calculator_id = flow.add_node(calculator.into());
+    // Connect splitter -> calculator
+    let num_splitter_outputs = flow.node(splitter_id).num_outputs();
+    for port_index in (0..num_splitter_outputs).map(PortIndex::new) {
+        flow.connect(
+            Socket {
+                node_id: splitter_id,
+                port_index,
+            },
+            Socket {
+                node_id: calculator_id,
+                port_index,
+            },
+        );
+    }
+    // Connect calculator -> printer
+    let num_calculator_outputs = flow.node(calculator_id).num_outputs();
+    for port_index in (0..num_calculator_outputs).map(PortIndex::new) {
+        flow.connect(
+            Socket {
+                node_id: calculator_id,
+                port_index,
+            },
+            Socket {
+                node_id: printer_id,
+                port_index,
+            },
+        );
+    }
+
+    for i in 0..10 {
+        // Backward pass
+        flow.process_backward_all().unwrap();
+
+        // Inject input values
+        if let CalcFlowNode::Splitter(splitter) = flow.node_mut(splitter_id) {
+            if splitter.is_input_requested() {
+                splitter.input_mut().incoming = Some(f64::from(i));
+            }
+        }
+
+        // Forward pass
+        flow.process_forward_all().unwrap();
+    }
+}
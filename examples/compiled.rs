@@ -0,0 +1,86 @@
+use flowcalc::{flow::*, node::*, port::*};
+
+use std::time::Instant;
+
+/// The number of parallel chains
+const WIDTH: usize = 50;
+
+/// The number of nodes in each chain
+const DEPTH: usize = 20;
+
+/// The number of repetitions of both passes
+const PASSES: usize = 1_000;
+
+flowcalc::node_enum! {
+    #[derive(Debug, PartialEq)]
+    enum BenchNode: Node<(), u64> {
+        Source(CounterSource<(), u64>),
+        Splitter(OneToManySplitter<(), u64>),
+        Diff(DiffNode<(), u64>),
+        Adder(AdderNode<(), u64>),
+        Sink(DropNode<(), u64>),
+    }
+}
+
+/// A single source fans out into many chains of nodes that
+/// are finally summed up into a single sink
+fn build_flow() -> Flow<BenchNode, (), u64> {
+    let mut flow = Flow::with_capacity(WIDTH * DEPTH + 4);
+    let source_id = flow.add_node(CounterSource::new(0, 1, None).into());
+    let splitter_id = flow.add_node(OneToManySplitter::new(WIDTH).into());
+    let adder_id = flow.add_node(AdderNode::new(WIDTH).into());
+    let sink_id = flow.add_node(DropNode::new(()).into());
+    flow.connect_simple(source_id, splitter_id);
+    flow.connect_simple(adder_id, sink_id);
+    for port_index in (0..WIDTH).map(PortIndex::new) {
+        let mut output = Socket {
+            node_id: splitter_id,
+            port_index,
+        };
+        for _ in 0..DEPTH {
+            let diff_id = flow.add_node(DiffNode::new().into());
+            flow.connect(
+                output,
+                Socket {
+                    node_id: diff_id,
+                    port_index: PortIndex::new(0),
+                },
+            );
+            output = Socket {
+                node_id: diff_id,
+                port_index: PortIndex::new(0),
+            };
+        }
+        flow.connect(
+            output,
+            Socket {
+                node_id: adder_id,
+                port_index,
+            },
+        );
+    }
+    flow
+}
+
+fn main() {
+    let mut flow = build_flow();
+    let started = Instant::now();
+    for _ in 0..PASSES {
+        flow.process_all().unwrap();
+    }
+    println!("Flow: {:?}", started.elapsed());
+
+    let mut compiled_flow = build_flow().compile().unwrap();
+    let started = Instant::now();
+    for _ in 0..PASSES {
+        compiled_flow.process_all();
+    }
+    println!("CompiledFlow: {:?}", started.elapsed());
+
+    // Both flow graphs must dispatch the same nodes and end up in
+    // the same state
+    assert_eq!(flow.process_all().unwrap(), compiled_flow.process_all());
+    for node_id in flow.node_ids() {
+        assert_eq!(flow.node(node_id), compiled_flow.node(node_id));
+    }
+}
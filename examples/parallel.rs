@@ -0,0 +1,187 @@
+use flowcalc::{flow::*, node::*, port::*};
+
+use std::time::Instant;
+
+/// The number of nodes in the wide section of the flow graph
+const WIDTH: usize = 1_000;
+
+/// The number of repetitions of both passes
+const PASSES: usize = 10;
+
+/// Simulates an expensive computation
+struct BusyNode {
+    input: Port<u64, ()>,
+    output: Port<(), u64>,
+    rounds: u64,
+}
+
+impl BusyNode {
+    fn new(rounds: u64) -> Self {
+        Self {
+            input: Port::new(),
+            output: Port::new(),
+            rounds,
+        }
+    }
+}
+
+impl Node<(), u64> for BusyNode {}
+
+impl NodeInputs<(), u64> for BusyNode {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        packet: Packet<u64, ()>,
+    ) {
+        self.input.accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<(), u64>> {
+        self.input.try_dispatch_packet()
+    }
+}
+
+impl NodeOutputs<(), u64> for BusyNode {
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<(), u64>,
+    ) {
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<u64, ()>> {
+        self.output.try_dispatch_packet()
+    }
+
+    fn reset_output_control(&mut self, _token: AccessToken, _output_index: PortIndex) {
+        self.output.incoming = None;
+    }
+}
+
+impl NodeProcessor for BusyNode {
+    fn process_outputs(&mut self, _token: AccessToken) {
+        self.input.outgoing = self.output.incoming.take();
+    }
+
+    fn process_inputs(&mut self, _token: AccessToken) {
+        self.output.outgoing = self.input.incoming.take().map(|mut value| {
+            for _ in 0..self.rounds {
+                value = value
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+            }
+            // Prevent overflows when summing up the results
+            value >> 32
+        });
+    }
+}
+
+flowcalc::node_enum! {
+    enum BenchNode: Node<(), u64> {
+        Source(FnSource<(), u64, fn() -> u64>),
+        Splitter(OneToManySplitter<(), u64>),
+        Busy(BusyNode),
+        Adder(AdderNode<(), u64>),
+        Sink(DropNode<(), u64>),
+    }
+}
+
+fn next_value() -> u64 {
+    1
+}
+
+/// A single source fans out into many nodes of varying cost that
+/// are finally summed up into a single sink
+fn wide_then_narrow() -> Flow<BenchNode, (), u64> {
+    let mut flow = Flow::with_capacity(WIDTH + 4);
+    let source_id = flow.add_node(BenchNode::Source(source_fn(next_value)));
+    let splitter_id = flow.add_node(OneToManySplitter::new(WIDTH).into());
+    let adder_id = flow.add_node(AdderNode::new(WIDTH).into());
+    let sink_id = flow.add_node(DropNode::new(()).into());
+    flow.connect_simple(source_id, splitter_id);
+    flow.connect_simple(adder_id, sink_id);
+    for port_index in (0..WIDTH).map(PortIndex::new) {
+        // Irregular costs
+        let rounds = 1_000 + (port_index.index() as u64 % 7) * 10_000;
+        let busy_id = flow.add_node(BusyNode::new(rounds).into());
+        flow.connect(
+            Socket {
+                node_id: splitter_id,
+                port_index,
+            },
+            Socket {
+                node_id: busy_id,
+                port_index: PortIndex::new(0),
+            },
+        );
+        flow.connect(
+            Socket {
+                node_id: busy_id,
+                port_index: PortIndex::new(0),
+            },
+            Socket {
+                node_id: adder_id,
+                port_index,
+            },
+        );
+    }
+    flow
+}
+
+fn main() {
+    let mut flow = wide_then_narrow();
+    let started = Instant::now();
+    for _ in 0..PASSES {
+        flow.process_all().unwrap();
+    }
+    println!("Sequential: {:?}", started.elapsed());
+
+    let num_threads = std::thread::available_parallelism()
+        .map(usize::from)
+        .unwrap_or(1);
+    let mut flow = wide_then_narrow();
+    let started = Instant::now();
+    for _ in 0..PASSES {
+        flow.process_all_parallel(num_threads).unwrap();
+    }
+    println!(
+        "Parallel ({} threads): {:?}",
+        num_threads,
+        started.elapsed()
+    );
+
+    // Level-synchronous execution for comparison, requires
+    // the additional feature "rayon"
+    #[cfg(feature = "rayon")]
+    {
+        let mut flow = wide_then_narrow();
+        let started = Instant::now();
+        for _ in 0..PASSES {
+            flow.execute_parallel().unwrap();
+        }
+        println!(
+            "Level-synchronous ({} threads): {:?}",
+            rayon::current_num_threads(),
+            started.elapsed()
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use flowcalc::{flow::*, node::*, port::*};
+
+flowcalc::node_enum! {
+    enum PipelineNode: Node<(), i64> {
+        Source(FnSource<(), i64, Box<dyn FnMut() -> i64>>),
+        Splitter(OneToManySplitter<(), i64>),
+        Diff(DiffNode<(), i64>),
+        Adder(AdderNode<(), i64>),
+        Sink(RingBufferSink<(), i64>),
+    }
+}
+
+fn main() {
+    // Generate the sequence of square numbers
+    let mut counter = 0;
+    let generate: Box<dyn FnMut() -> i64> = Box::new(move || {
+        counter += 1;
+        counter * counter
+    });
+
+    // source -> splitter -+-> diff -+-> adder -> sink
+    //                     |         |
+    //                     +---------+
+    let mut flow: Flow<PipelineNode, (), i64> = Flow::new();
+    let source_id = flow.add_node(source_fn(generate).into());
+    let splitter_id = flow.add_node(OneToManySplitter::new(2).into());
+    let diff_id = flow.add_node(DiffNode::new().into());
+    let adder_id = flow.add_node(AdderNode::new(2).into());
+    let sink_id = flow.add_node(RingBufferSink::with_capacity(1, 3).into());
+    flow.connect_simple(source_id, splitter_id);
+    flow.connect_simple(splitter_id, diff_id);
+    flow.connect_simple(diff_id, adder_id);
+    flow.connect(
+        Socket {
+            node_id: splitter_id,
+            port_index: PortIndex::new(1),
+        },
+        Socket {
+            node_id: adder_id,
+            port_index: PortIndex::new(1),
+        },
+    );
+    flow.connect_simple(adder_id, sink_id);
+
+    let sink_input = Socket {
+        node_id: sink_id,
+        port_index: PortIndex::new(0),
+    };
+    for _ in 0..5 {
+        // The sink requests a value for the next pass
+        flow.with_input_port_mut(sink_input, |port| port.outgoing = Some(()));
+
+        // Backward pass: Propagate the request from the sink
+        // back to the source
+        flow.process_backward_all().unwrap();
+
+        // Forward pass: Generate a new value and propagate it
+        // from the source to the sink
+        flow.process_forward_all().unwrap();
+
+        if let PipelineNode::Sink(sink) = flow.node(sink_id) {
+            println!("{:?}", sink.recent(PortIndex::new(0)));
+        }
+    }
+}
@@ -10,91 +10,11 @@ enum Value {
     GrayImage(image::GrayImage),
 }
 
-#[derive(Debug)]
-struct RandomAsciiTextSource {
-    num_chars: usize,
-    rng: ThreadRng,
-    output: Port<(), Value>,
-}
-
-impl RandomAsciiTextSource {
-    fn new(num_chars: usize) -> Self {
-        Self {
-            num_chars,
-            rng: rand::thread_rng(),
-            output: Port::new(),
-        }
-    }
-
-    fn gen_text(&mut self) -> String {
-        let num_chars = self.num_chars;
-        std::iter::repeat(())
-            .map(|()| self.rng.sample(rand::distributions::Alphanumeric))
-            .take(num_chars)
-            .collect()
-    }
-}
-
-impl Node<(), Value> for RandomAsciiTextSource {}
-
-impl NodeInputs<(), Value> for RandomAsciiTextSource {
-    fn num_inputs(&self) -> usize {
-        0
-    }
-
-    fn accept_input_packet(
-        &mut self,
-        _token: AccessToken,
-        _input_index: PortIndex,
-        _packet: Packet<Value, ()>,
-    ) {
-        unimplemented!();
-    }
-
-    fn try_dispatch_input_packet(
-        &mut self,
-        _token: AccessToken,
-        _input_index: PortIndex,
-    ) -> Option<Packet<(), Value>> {
-        unimplemented!();
-    }
-}
-
-impl NodeOutputs<(), Value> for RandomAsciiTextSource {
-    fn num_outputs(&self) -> usize {
-        1
-    }
-
-    fn accept_output_packet(
-        &mut self,
-        _token: AccessToken,
-        _output_index: PortIndex,
-        packet: Packet<(), Value>,
-    ) {
-        self.output.accept_packet(packet);
-    }
-
-    fn try_dispatch_output_packet(
-        &mut self,
-        _token: AccessToken,
-        _output_index: PortIndex,
-    ) -> Option<Packet<Value, ()>> {
-        self.output.try_dispatch_packet()
-    }
-}
-
-impl NodeProcessor for RandomAsciiTextSource {
-    fn process_inputs(&mut self, _: AccessToken) {
-        if self.output.incoming.is_none() {
-            return;
-        }
-        let text = self.gen_text();
-        self.output.outgoing = Some(Value::Text(text));
-    }
-
-    fn process_outputs(&mut self, _: AccessToken) {
-        // nothing to do
-    }
+fn gen_text(rng: &mut ThreadRng, num_chars: usize) -> String {
+    std::iter::repeat(())
+        .map(|()| rng.sample(rand::distributions::Alphanumeric))
+        .take(num_chars)
+        .collect()
 }
 
 #[derive(Debug)]
@@ -158,6 +78,10 @@ impl NodeOutputs<(), Value> for TextQrEncoder {
     ) -> Option<Packet<Value, ()>> {
         self.output.try_dispatch_packet()
     }
+
+    fn reset_output_control(&mut self, _token: AccessToken, _output_index: PortIndex) {
+        self.output.incoming = None;
+    }
 }
 
 impl NodeProcessor for TextQrEncoder {
@@ -242,6 +166,10 @@ impl NodeOutputs<(), Value> for QrTextDecoder {
     ) -> Option<Packet<Value, ()>> {
         self.output.try_dispatch_packet()
     }
+
+    fn reset_output_control(&mut self, _token: AccessToken, _output_index: PortIndex) {
+        self.output.incoming = None;
+    }
 }
 
 impl NodeProcessor for QrTextDecoder {
@@ -273,7 +201,10 @@ impl NodeProcessor for QrTextDecoder {
 }
 
 fn main() {
-    let source = Rc::new(RefCell::new(RandomAsciiTextSource::new(20)));
+    let mut rng = rand::thread_rng();
+    let source = Rc::new(RefCell::new(source_fn(move || {
+        Value::Text(gen_text(&mut rng, 20))
+    })));
     let encoder = Rc::new(RefCell::new(TextQrEncoder::new()));
     let decoder = Rc::new(RefCell::new(QrTextDecoder::new()));
     let splitter = Rc::new(RefCell::new(OneToManySplitter::<(), Value>::new(2)));
@@ -285,26 +216,8 @@ fn main() {
     let decoder_id = flow.add_node(RcProxyNode::new(Rc::clone(&decoder) as _));
     let splitter_id = flow.add_node(RcProxyNode::new(Rc::clone(&splitter) as _));
     let printer_id = flow.add_node(RcProxyNode::new(Rc::clone(&printer) as _));
-    flow.connect(
-        Socket {
-            node_id: source_id,
-            port_index: PortIndex::new(0),
-        },
-        Socket {
-            node_id: splitter_id,
-            port_index: PortIndex::new(0),
-        },
-    );
-    flow.connect(
-        Socket {
-            node_id: splitter_id,
-            port_index: PortIndex::new(0),
-        },
-        Socket {
-            node_id: encoder_id,
-            port_index: PortIndex::new(0),
-        },
-    );
+    flow.connect_simple(source_id, splitter_id);
+    flow.connect_simple(splitter_id, encoder_id);
     flow.connect(
         Socket {
             node_id: splitter_id,
@@ -315,16 +228,7 @@ fn main() {
             port_index: PortIndex::new(0),
         },
     );
-    flow.connect(
-        Socket {
-            node_id: encoder_id,
-            port_index: PortIndex::new(0),
-        },
-        Socket {
-            node_id: decoder_id,
-            port_index: PortIndex::new(0),
-        },
-    );
+    flow.connect_simple(encoder_id, decoder_id);
     flow.connect(
         Socket {
             node_id: decoder_id,
@@ -337,24 +241,13 @@ fn main() {
     );
 
     // Activate all sink inputs
-    for port in printer.borrow_mut().inputs.ports_mut() {
-        port.outgoing = Some(());
-    }
-
-    let topo_nodes = flow.topological_nodes().unwrap();
+    printer.borrow_mut().request_all_inputs(());
 
     for _ in 0..10 {
         let now = Instant::now();
 
-        // Backward pass
-        for node in topo_nodes.iter().rev() {
-            flow.process_outputs(*node);
-        }
-
-        // Forward pass
-        for node in topo_nodes.iter() {
-            flow.process_inputs(*node);
-        }
+        // Backward and forward pass
+        flow.execute().unwrap();
 
         println!("Duration: {} ms", now.elapsed().as_micros() as f64 / 1000.0);
     }
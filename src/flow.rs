@@ -1,6 +1,32 @@
-use crate::{node::*, port::*, SealedTag};
+use crate::{node::*, port::*, testing::ProcessEvent, SealedTag};
 
-use std::{collections::HashMap, marker::PhantomData};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    marker::PhantomData,
+    rc::Rc,
+};
+
+mod compiled;
+
+mod composite;
+
+mod dot;
+
+#[cfg(feature = "rayon")]
+mod layered;
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+mod topology;
+
+pub use self::{
+    compiled::CompiledFlow,
+    composite::CompositeNode,
+    topology::{FlowTopology, TopologyError},
+};
 
 /// Private access token of `Flow` to access sealed
 /// methods in `Node`.
@@ -9,6 +35,8 @@ use std::{collections::HashMap, marker::PhantomData};
 /// methods in traits to the crate that defines the trait.
 #[derive(Debug)]
 pub struct AccessToken {
+    // Never read, only prevents construction outside of this module
+    #[allow(dead_code)]
     tag: SealedTag,
 }
 
@@ -19,7 +47,8 @@ impl AccessToken {
 }
 
 /// Node identifier in a flow graph
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeId(usize);
 
 impl NodeId {
@@ -38,7 +67,8 @@ impl From<NodeId> for usize {
 ///
 /// The type of port (input or output) is unspecified
 /// and implicitly follows from the context.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Socket {
     /// The node identifier
     pub node_id: NodeId,
@@ -47,32 +77,176 @@ pub struct Socket {
     pub port_index: PortIndex,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct FlowNode<N> {
     node: N,
     connected_inputs: HashMap<PortIndex, Socket>,
     connected_outputs: HashMap<PortIndex, Socket>,
 }
 
+/// Callback for observing the payload of packets that
+/// traverse a connection in forward direction
+pub type EdgeProbeFn<P> = Box<dyn FnMut(&P)>;
+
+struct EdgeProbe<P>(EdgeProbeFn<P>);
+
+impl<P> fmt::Debug for EdgeProbe<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EdgeProbe")
+    }
+}
+
 /// Directed acyclic graph (DAG) of computational nodes
 #[derive(Debug, Default)]
 pub struct Flow<N, S, P> {
-    nodes: Vec<FlowNode<N>>,
+    // Removed nodes leave a gap to keep the ids of all other
+    // nodes stable
+    nodes: Vec<Option<FlowNode<N>>>,
+    edge_probes: HashMap<Socket, EdgeProbe<P>>,
+    topological_order: Option<Vec<NodeId>>,
+    process_events: Option<Vec<ProcessEvent>>,
+    dirty_nodes: HashSet<NodeId>,
+    // The most recent values that have been passed on by
+    // each output during incremental execution
+    cached_outputs: HashMap<Socket, P>,
     phantom1: PhantomData<S>,
     phantom2: PhantomData<P>,
 }
 
+/// Snapshot of both the structure and the state of a flow graph
+///
+/// The snapshot contains clones of all nodes including the current
+/// values of their ports. Snapshots could be compared to determine
+/// what has changed between subsequent passes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphSnapshot<N> {
+    nodes: Vec<Option<FlowNode<N>>>,
+}
+
+impl<N> GraphSnapshot<N> {
+    /// The number of nodes in the snapshot
+    pub fn num_nodes(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|flow_node| flow_node.is_some())
+            .count()
+    }
+
+    /// Access a node in the snapshot
+    ///
+    /// Panics if the node has been removed.
+    pub fn node(&self, node_id: NodeId) -> &N {
+        &self.nodes[usize::from(node_id)]
+            .as_ref()
+            .expect("node has not been removed")
+            .node
+    }
+
+    /// Collect all nodes that differ from another snapshot
+    ///
+    /// A node is considered as changed if either its state or its
+    /// connections differ. Nodes that are only contained in one of
+    /// the snapshots, e.g. after they have been removed, are
+    /// considered as changed.
+    pub fn changed_nodes(&self, other: &Self) -> Vec<NodeId>
+    where
+        N: PartialEq,
+    {
+        (0..self.nodes.len().max(other.nodes.len()))
+            .filter(|&index| self.nodes.get(index) != other.nodes.get(index))
+            .map(NodeId::new)
+            .collect()
+    }
+}
+
 /// Detected cycle
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Cycle {
-    node: NodeId,
+    path: Vec<NodeId>,
 }
 
 impl Cycle {
     /// A node within the cycle
-    pub fn node(self) -> NodeId {
-        self.node
+    ///
+    /// The first node of `path()`.
+    pub fn node(&self) -> NodeId {
+        self.path[0]
     }
+
+    /// The nodes that form the cycle in the direction of
+    /// their connections
+    ///
+    /// The path starts and ends with the same node, i.e. it
+    /// contains a single node twice for self-loops.
+    pub fn path(&self) -> &[NodeId] {
+        &self.path
+    }
+}
+
+/// The two phases for processing a flow graph
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum Phase {
+    /// 1st phase: Propagate control from outputs to inputs
+    Backward,
+
+    /// 2nd phase: Propagate data from inputs to outputs
+    Forward,
+}
+
+/// Rejected connection
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ConnectError {
+    /// The node does not exist or has been removed
+    UnknownNode {
+        /// The node
+        node_id: NodeId,
+    },
+
+    /// The output and the input belong to the same node
+    ReflexiveConnection {
+        /// The node
+        node_id: NodeId,
+    },
+
+    /// The port does not exist
+    PortIndexOutOfRange {
+        /// The output or input socket
+        socket: Socket,
+
+        /// The number of outputs or inputs of the node
+        num_ports: usize,
+    },
+
+    /// The output is already connected to another input
+    OutputAlreadyConnected {
+        /// The output socket
+        output: Socket,
+
+        /// The input socket of the existing connection
+        input: Socket,
+    },
+
+    /// The input is already connected to another output
+    InputAlreadyConnected {
+        /// The input socket
+        input: Socket,
+
+        /// The output socket of the existing connection
+        output: Socket,
+    },
+
+    /// The input is already connected to the maximum
+    /// number of outputs
+    FanInExceeded {
+        /// The input socket
+        input: Socket,
+
+        /// The maximum number of connections
+        max_fan_in: usize,
+    },
+
+    /// The connection would close a cycle
+    WouldCreateCycle(Cycle),
 }
 
 impl<N, S, P> Flow<N, S, P>
@@ -82,6 +256,11 @@ where
     pub fn new() -> Self {
         Self {
             nodes: Vec::new(),
+            edge_probes: HashMap::new(),
+            topological_order: None,
+            process_events: None,
+            dirty_nodes: HashSet::new(),
+            cached_outputs: HashMap::new(),
             phantom1: PhantomData,
             phantom2: PhantomData,
         }
@@ -90,6 +269,11 @@ where
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             nodes: Vec::with_capacity(capacity),
+            edge_probes: HashMap::new(),
+            topological_order: None,
+            process_events: None,
+            dirty_nodes: HashSet::new(),
+            cached_outputs: HashMap::new(),
             phantom1: PhantomData,
             phantom2: PhantomData,
         }
@@ -101,10 +285,65 @@ where
             connected_inputs: Default::default(),
             connected_outputs: Default::default(),
         };
-        self.nodes.push(new_node);
+        self.nodes.push(Some(new_node));
+        self.invalidate_topology();
         NodeId::new(self.nodes.len() - 1)
     }
 
+    /// Remove a node from the flow graph
+    ///
+    /// All connections of the node are removed before the node is
+    /// taken out of the flow graph, including any edge probes of its
+    /// outputs. The ids of all other nodes remain valid and the id of
+    /// the removed node is never reused.
+    ///
+    /// Returns `None` if the node has already been removed.
+    pub fn remove_node(&mut self, node_id: NodeId) -> Option<N> {
+        let flow_node = self.nodes.get(usize::from(node_id))?.as_ref()?;
+        let inputs: Vec<_> = flow_node
+            .connected_inputs
+            .keys()
+            .map(|port_index| Socket {
+                node_id,
+                port_index: *port_index,
+            })
+            .collect();
+        let outputs: Vec<_> = flow_node
+            .connected_outputs
+            .keys()
+            .map(|port_index| Socket {
+                node_id,
+                port_index: *port_index,
+            })
+            .collect();
+        for input in inputs {
+            self.disconnect_input(input);
+        }
+        for output in outputs {
+            self.disconnect_output(output);
+        }
+        self.edge_probes
+            .retain(|output, _| output.node_id != node_id);
+        self.dirty_nodes.remove(&node_id);
+        self.invalidate_topology();
+        self.nodes[usize::from(node_id)]
+            .take()
+            .map(|flow_node| flow_node.node)
+    }
+
+    /// Check if a node exists and has not been removed
+    pub fn contains_node(&self, node_id: NodeId) -> bool {
+        self.nodes
+            .get(usize::from(node_id))
+            .is_some_and(Option::is_some)
+    }
+
+    /// The ids of all nodes that have not been removed in
+    /// ascending order
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.flow_nodes().map(|(node_id, _)| node_id)
+    }
+
     pub fn node(&self, node_id: NodeId) -> &N {
         &self.flow_node(node_id).node
     }
@@ -113,12 +352,303 @@ where
         &mut self.flow_node_mut(node_id).node
     }
 
+    /// Exchange the computations of two nodes
+    ///
+    /// Only the nodes are swapped while all connections stay in
+    /// place, i.e. each node continues with the connections of
+    /// the other node. Both nodes are supposed to provide the same
+    /// number of inputs and outputs.
+    ///
+    /// Both nodes are notified about the disconnection of all their
+    /// previous connections before swapping them, discarding the
+    /// control of their outputs. Afterwards they are notified about
+    /// all connections in their new place.
+    pub fn swap_nodes(&mut self, lhs: NodeId, rhs: NodeId) {
+        debug_assert_eq!(self.node(lhs).num_inputs(), self.node(rhs).num_inputs());
+        debug_assert_eq!(self.node(lhs).num_outputs(), self.node(rhs).num_outputs());
+        if lhs == rhs {
+            return;
+        }
+        self.notify_all_disconnected(lhs);
+        self.notify_all_disconnected(rhs);
+        let (lhs_flow_node, rhs_flow_node) =
+            disjoint_pair_mut(&mut self.nodes, usize::from(lhs), usize::from(rhs));
+        std::mem::swap(&mut lhs_flow_node.node, &mut rhs_flow_node.node);
+        self.forget_cached_outputs(lhs);
+        self.forget_cached_outputs(rhs);
+        self.notify_all_connected(lhs);
+        self.notify_all_connected(rhs);
+    }
+
+    /// The indexes of all connected inputs and outputs in
+    /// ascending order
+    fn connected_port_indexes(&self, node_id: NodeId) -> (Vec<PortIndex>, Vec<PortIndex>) {
+        let flow_node = self.flow_node(node_id);
+        let mut input_indexes: Vec<_> = flow_node.connected_inputs.keys().copied().collect();
+        input_indexes.sort_unstable();
+        let mut output_indexes: Vec<_> = flow_node.connected_outputs.keys().copied().collect();
+        output_indexes.sort_unstable();
+        (input_indexes, output_indexes)
+    }
+
+    fn notify_all_disconnected(&mut self, node_id: NodeId) {
+        let (input_indexes, output_indexes) = self.connected_port_indexes(node_id);
+        let node = self.node_mut(node_id);
+        for output_index in output_indexes {
+            node.reset_output_control(AccessToken::new(), output_index);
+            node.on_output_disconnected(AccessToken::new(), output_index);
+        }
+        for input_index in input_indexes {
+            node.on_input_disconnected(AccessToken::new(), input_index);
+        }
+    }
+
+    fn notify_all_connected(&mut self, node_id: NodeId) {
+        let (input_indexes, output_indexes) = self.connected_port_indexes(node_id);
+        let node = self.node_mut(node_id);
+        for output_index in output_indexes {
+            node.on_output_connected(AccessToken::new(), output_index);
+        }
+        for input_index in input_indexes {
+            node.on_input_connected(AccessToken::new(), input_index);
+        }
+    }
+
+    /// Change the number of inputs and outputs of a node
+    ///
+    /// Connections of surplus ports are removed before the node
+    /// is resized. The node is only resized if the corresponding
+    /// number of ports actually changes. Nodes with a fixed number
+    /// of ports will panic when trying to resize them.
+    pub fn set_node_arity(&mut self, node_id: NodeId, num_inputs: usize, num_outputs: usize) {
+        let flow_node = self.flow_node(node_id);
+        let surplus_inputs: Vec<_> = flow_node
+            .connected_inputs
+            .keys()
+            .filter(|port_index| usize::from(**port_index) >= num_inputs)
+            .copied()
+            .collect();
+        let surplus_outputs: Vec<_> = flow_node
+            .connected_outputs
+            .keys()
+            .filter(|port_index| usize::from(**port_index) >= num_outputs)
+            .copied()
+            .collect();
+        for port_index in surplus_inputs {
+            self.disconnect_input(Socket {
+                node_id,
+                port_index,
+            });
+        }
+        for port_index in surplus_outputs {
+            self.disconnect_output(Socket {
+                node_id,
+                port_index,
+            });
+        }
+        let node = self.node_mut(node_id);
+        if node.num_inputs() != num_inputs {
+            node.resize_inputs(AccessToken::new(), num_inputs);
+            debug_assert_eq!(num_inputs, node.num_inputs());
+        }
+        if node.num_outputs() != num_outputs {
+            node.resize_outputs(AccessToken::new(), num_outputs);
+            debug_assert_eq!(num_outputs, node.num_outputs());
+        }
+    }
+
+    /// Activate all outputs of a node
+    ///
+    /// The control is delivered to each output port as if it
+    /// has been received from a connected successor during
+    /// the backward pass.
+    pub fn request_all_outputs(&mut self, node_id: NodeId, ctrl: S)
+    where
+        S: Clone,
+    {
+        let node = self.node_mut(node_id);
+        for output_index in (0..node.num_outputs()).map(PortIndex::new) {
+            let packet = Packet {
+                payload: ctrl.clone(),
+                piggyback: None,
+            };
+            node.accept_output_packet(AccessToken::new(), output_index, packet);
+        }
+    }
+
+    /// Access an input port of a node for inspection or modification
+    ///
+    /// Returns `None` if the port is either not accessible or doesn't
+    /// exist.
+    ///
+    /// See also: `NodeInputs::with_input_port_mut()`
+    pub fn with_input_port_mut<R>(
+        &mut self,
+        input: Socket,
+        f: impl FnOnce(&mut Port<P, S>) -> R,
+    ) -> Option<R> {
+        let mut f = Some(f);
+        let mut result = None;
+        self.node_mut(input.node_id)
+            .with_input_port_mut(input.port_index, &mut |port| {
+                if let Some(f) = f.take() {
+                    result = Some(f(port));
+                }
+            });
+        result
+    }
+
+    /// Access an output port of a node for inspection or modification
+    ///
+    /// Returns `None` if the port is either not accessible or doesn't
+    /// exist.
+    ///
+    /// See also: `NodeOutputs::with_output_port_mut()`
+    pub fn with_output_port_mut<R>(
+        &mut self,
+        output: Socket,
+        f: impl FnOnce(&mut Port<S, P>) -> R,
+    ) -> Option<R> {
+        let mut f = Some(f);
+        let mut result = None;
+        self.node_mut(output.node_id)
+            .with_output_port_mut(output.port_index, &mut |port| {
+                if let Some(f) = f.take() {
+                    result = Some(f(port));
+                }
+            });
+        result
+    }
+
+    /// Downcast a node into its concrete type
+    ///
+    /// Returns `None` if the node is not of type `T`. Please note
+    /// that proxy nodes like `RcProxyNode` are not transparent,
+    /// i.e. only the proxy itself could be obtained.
+    pub fn node_as<T>(&self, node_id: NodeId) -> Option<&T>
+    where
+        T: 'static,
+    {
+        self.node(node_id).as_any().downcast_ref()
+    }
+
+    /// Downcast a node into its concrete type for modification
+    ///
+    /// See also: `node_as()`
+    pub fn node_as_mut<T>(&mut self, node_id: NodeId) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        self.node_mut(node_id).as_any_mut().downcast_mut()
+    }
+
+    /// Capture a snapshot of both the structure and the state
+    /// of the flow graph
+    pub fn snapshot(&self) -> GraphSnapshot<N>
+    where
+        N: Clone,
+    {
+        GraphSnapshot {
+            nodes: self.nodes.clone(),
+        }
+    }
+
+    /// Restore both the structure and the state of the flow graph
+    /// from a snapshot
+    ///
+    /// Edge probes are not affected. The cached values for
+    /// incremental execution are discarded and all nodes are
+    /// marked as dirty, i.e. the next incremental execution
+    /// processes all nodes.
+    ///
+    /// See also: `execute_incremental()`
+    pub fn restore(&mut self, snapshot: &GraphSnapshot<N>)
+    where
+        N: Clone,
+    {
+        self.nodes = snapshot.nodes.clone();
+        self.cached_outputs.clear();
+        self.dirty_nodes.clear();
+        self.mark_all_dirty();
+        self.invalidate_topology();
+    }
+
+    /// Transform all nodes while preserving the topology
+    ///
+    /// Applies `f` to each node in order of their ids. All
+    /// connections, edge probes and the cached topological
+    /// order are retained, because they only refer to node ids.
+    ///
+    /// Could be used for wrapping nodes uniformly, e.g. into
+    /// proxy nodes or boxed trait objects.
+    pub fn map_nodes<M, F>(self, mut f: F) -> Flow<M, S, P>
+    where
+        M: Node<S, P>,
+        F: FnMut(N) -> M,
+    {
+        let Self {
+            nodes,
+            edge_probes,
+            topological_order,
+            process_events,
+            dirty_nodes,
+            cached_outputs,
+            phantom1,
+            phantom2,
+        } = self;
+        let nodes = nodes
+            .into_iter()
+            .map(|flow_node| {
+                flow_node.map(|flow_node| {
+                    let FlowNode {
+                        node,
+                        connected_inputs,
+                        connected_outputs,
+                    } = flow_node;
+                    FlowNode {
+                        node: f(node),
+                        connected_inputs,
+                        connected_outputs,
+                    }
+                })
+            })
+            .collect();
+        Flow {
+            nodes,
+            edge_probes,
+            topological_order,
+            process_events,
+            dirty_nodes,
+            cached_outputs,
+            phantom1,
+            phantom2,
+        }
+    }
+
+    /// Panics if the node has been removed
     fn flow_node(&self, node_id: NodeId) -> &FlowNode<N> {
-        &self.nodes[usize::from(node_id)]
+        self.nodes[usize::from(node_id)]
+            .as_ref()
+            .expect("node has not been removed")
     }
 
+    /// Panics if the node has been removed
     fn flow_node_mut(&mut self, node_id: NodeId) -> &mut FlowNode<N> {
-        &mut self.nodes[usize::from(node_id)]
+        self.nodes[usize::from(node_id)]
+            .as_mut()
+            .expect("node has not been removed")
+    }
+
+    /// All nodes that have not been removed in ascending order
+    fn flow_nodes(&self) -> impl Iterator<Item = (NodeId, &FlowNode<N>)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, flow_node)| {
+                flow_node
+                    .as_ref()
+                    .map(|flow_node| (NodeId::new(index), flow_node))
+            })
     }
 
     /// Remove a connection from an output socket
@@ -126,7 +656,8 @@ where
     /// Returns the input socket of the subsequent node
     /// at the opposite end of the connection.
     ///
-    /// State or values of disconnected ports on both
+    /// The control of the disconnected output is discarded.
+    /// Otherwise state or values of disconnected ports on both
     /// ends of a removed connection are not modified.
     pub fn disconnect_output(&mut self, output: Socket) -> Option<Socket> {
         let Socket {
@@ -136,6 +667,7 @@ where
         let node = self.flow_node_mut(node_id);
         let connected_input = node.connected_outputs.remove(&port_index);
         if let Some(input) = connected_input {
+            self.invalidate_topology();
             let Socket {
                 node_id,
                 port_index,
@@ -143,6 +675,8 @@ where
             let node = self.flow_node_mut(node_id);
             let _connected_output = node.connected_inputs.remove(&port_index);
             debug_assert_eq!(_connected_output, Some(output));
+            self.cached_outputs.remove(&output);
+            self.notify_disconnected(output, input);
         }
         connected_input
     }
@@ -152,7 +686,8 @@ where
     /// Returns the output socket of the preceding node
     /// at the opposite end of the connection.
     ///
-    /// State or values of disconnected ports on both
+    /// The control of the disconnected output is discarded.
+    /// Otherwise state or values of disconnected ports on both
     /// ends of a removed connection are not modified.
     pub fn disconnect_input(&mut self, input: Socket) -> Option<Socket> {
         let Socket {
@@ -162,6 +697,7 @@ where
         let node = self.flow_node_mut(node_id);
         let connected_output = node.connected_inputs.remove(&port_index);
         if let Some(output) = connected_output {
+            self.invalidate_topology();
             let Socket {
                 node_id,
                 port_index,
@@ -169,6 +705,8 @@ where
             let node = self.flow_node_mut(node_id);
             let _connected_input = node.connected_outputs.remove(&port_index);
             debug_assert_eq!(_connected_input, Some(input));
+            self.cached_outputs.remove(&output);
+            self.notify_disconnected(output, input);
         }
         connected_output
     }
@@ -179,12 +717,90 @@ where
     /// Connections are only permitted between distinct nodes,
     /// i.e. neither reflexive connections nor cycles are allowed.
     ///
-    /// The caller is responsible to ensure that no cycles are
-    /// introduced by the new connection! Otherwise a debug
-    /// assertion is triggered.
+    /// Panics if the connection is rejected, see `try_connect()`.
     pub fn connect(&mut self, output: Socket, input: Socket) {
-        // Check for reflexive connections upfront
-        debug_assert_ne!(output.node_id, input.node_id);
+        if let Err(err) = self.try_connect(output, input) {
+            panic!("Failed to connect {:?} with {:?}: {:?}", output, input, err);
+        }
+    }
+
+    /// Try to establish a connection between an output port of
+    /// a preceding node and an input port of a subsequent node
+    ///
+    /// The number of connections per input is limited by the
+    /// `max_fan_in()` of the subsequent node. Connections that
+    /// exceed this limit are rejected with `FanInExceeded`. The
+    /// flow graph stores at most a single connection per input,
+    /// i.e. connecting an input that accepts more connections
+    /// is rejected with `InputAlreadyConnected` if it is already
+    /// connected. Existing connections are never replaced
+    /// implicitly, please use `reconnect()` for this purpose.
+    ///
+    /// Both the output and the input must not be connected yet.
+    ///
+    /// The connection is validated before modifying the flow graph.
+    /// A rejected connection leaves the flow graph untouched.
+    ///
+    /// See also: `connect()`
+    pub fn try_connect(&mut self, output: Socket, input: Socket) -> Result<(), ConnectError> {
+        for node_id in [output.node_id, input.node_id].iter().copied() {
+            if !self.contains_node(node_id) {
+                return Err(ConnectError::UnknownNode { node_id });
+            }
+        }
+        if output.node_id == input.node_id {
+            return Err(ConnectError::ReflexiveConnection {
+                node_id: output.node_id,
+            });
+        }
+        // Check that both ports exist
+        let num_outputs = self.node(output.node_id).num_outputs();
+        if output.port_index >= num_outputs {
+            return Err(ConnectError::PortIndexOutOfRange {
+                socket: output,
+                num_ports: num_outputs,
+            });
+        }
+        let num_inputs = self.node(input.node_id).num_inputs();
+        if input.port_index >= num_inputs {
+            return Err(ConnectError::PortIndexOutOfRange {
+                socket: input,
+                num_ports: num_inputs,
+            });
+        }
+        // Check that the output is still available
+        if let Some(connected_input) = self
+            .flow_node(output.node_id)
+            .connected_outputs
+            .get(&output.port_index)
+        {
+            return Err(ConnectError::OutputAlreadyConnected {
+                output,
+                input: *connected_input,
+            });
+        }
+        // Check that the input accepts another connection
+        let input_node = self.flow_node(input.node_id);
+        let connected_output = input_node.connected_inputs.get(&input.port_index);
+        let fan_in = if connected_output.is_some() { 1 } else { 0 };
+        if let Some(max_fan_in) = input_node.node.max_fan_in(input.port_index) {
+            if fan_in >= max_fan_in {
+                return Err(ConnectError::FanInExceeded { input, max_fan_in });
+            }
+        }
+        // Only a single connection per input could be stored
+        if let Some(connected_output) = connected_output {
+            return Err(ConnectError::InputAlreadyConnected {
+                input,
+                output: *connected_output,
+            });
+        }
+        // Check for no cycles, i.e. the preceding node must not
+        // be reachable from the subsequent node
+        if let Some(mut path) = self.find_path(input.node_id, output.node_id) {
+            path.insert(0, output.node_id);
+            return Err(ConnectError::WouldCreateCycle(Cycle { path }));
+        }
         // Connect output port
         let output_node = self.flow_node_mut(output.node_id);
         let output_index = output.port_index;
@@ -193,8 +809,127 @@ where
         let input_node = self.flow_node_mut(input.node_id);
         let input_index = input.port_index;
         input_node.connected_inputs.insert(input_index, output);
-        // Check for no cycles
+        self.invalidate_topology();
         debug_assert!(self.topological_nodes().is_ok());
+        // Notify both nodes
+        self.node_mut(output.node_id)
+            .on_output_connected(AccessToken::new(), output.port_index);
+        self.node_mut(input.node_id)
+            .on_input_connected(AccessToken::new(), input.port_index);
+        Ok(())
+    }
+
+    /// Find the shortest path along the connections between
+    /// two nodes, including both nodes
+    fn find_path(&self, from: NodeId, to: NodeId) -> Option<Vec<NodeId>> {
+        let mut predecessors = vec![None; self.nodes.len()];
+        let mut pending = VecDeque::new();
+        pending.push_back(from);
+        while let Some(node_id) = pending.pop_front() {
+            if node_id == to {
+                let mut path = vec![to];
+                let mut node_id = to;
+                while let Some(predecessor) = predecessors[usize::from(node_id)] {
+                    path.push(predecessor);
+                    node_id = predecessor;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for input in self.flow_node(node_id).connected_outputs.values() {
+                let index = usize::from(input.node_id);
+                if input.node_id != from && predecessors[index].is_none() {
+                    predecessors[index] = Some(node_id);
+                    pending.push_back(input.node_id);
+                }
+            }
+        }
+        None
+    }
+
+    /// Connect the first output of a preceding node with
+    /// the first input of a subsequent node
+    ///
+    /// Shortcut for the common case of linear pipelines.
+    /// See also: `connect()`
+    pub fn connect_simple(&mut self, from: NodeId, to: NodeId) {
+        debug_assert!(self.node(from).num_outputs() > 0);
+        debug_assert!(self.node(to).num_inputs() > 0);
+        self.connect(
+            Socket {
+                node_id: from,
+                port_index: PortIndex::new(0),
+            },
+            Socket {
+                node_id: to,
+                port_index: PortIndex::new(0),
+            },
+        );
+    }
+
+    fn notify_disconnected(&mut self, output: Socket, input: Socket) {
+        let output_node = self.node_mut(output.node_id);
+        // The control of a disconnected output would otherwise
+        // continue to activate the node
+        output_node.reset_output_control(AccessToken::new(), output.port_index);
+        output_node.on_output_disconnected(AccessToken::new(), output.port_index);
+        self.node_mut(input.node_id)
+            .on_input_disconnected(AccessToken::new(), input.port_index);
+    }
+
+    /// Connect multiple outputs to a single input by summing
+    /// up their values
+    ///
+    /// A new `AdderNode` is added to the flow graph. All outputs
+    /// are connected to the inputs of this node in the given order
+    /// and its output is connected to the input. Returns the id of
+    /// the new node.
+    pub fn connect_summing(&mut self, outputs: &[Socket], input: Socket) -> NodeId
+    where
+        N: From<AdderNode<S, P>>,
+    {
+        let adder_id = self.add_node(AdderNode::new(outputs.len()).into());
+        for (port_index, output) in outputs.iter().enumerate() {
+            self.connect(
+                *output,
+                Socket {
+                    node_id: adder_id,
+                    port_index: PortIndex::new(port_index),
+                },
+            );
+        }
+        self.connect(
+            Socket {
+                node_id: adder_id,
+                port_index: PortIndex::new(0),
+            },
+            input,
+        );
+        adder_id
+    }
+
+    /// Observe the payload of all packets that are dispatched
+    /// from an output socket during the forward pass
+    ///
+    /// The probe is invoked with a reference to the payload before
+    /// the packet is handed over to the connected input. At most
+    /// one probe could be registered per output socket. A previously
+    /// registered probe is replaced and returned.
+    pub fn set_edge_probe(
+        &mut self,
+        output: Socket,
+        probe: EdgeProbeFn<P>,
+    ) -> Option<EdgeProbeFn<P>> {
+        self.edge_probes
+            .insert(output, EdgeProbe(probe))
+            .map(|EdgeProbe(probe)| probe)
+    }
+
+    /// Remove the probe from an output socket
+    pub fn remove_edge_probe(&mut self, output: Socket) -> Option<EdgeProbeFn<P>> {
+        self.edge_probes
+            .remove(&output)
+            .map(|EdgeProbe(probe)| probe)
     }
 
     pub fn reconnect(&mut self, output: Socket, input: Socket) {
@@ -213,12 +948,7 @@ where
         let mut candidates = Vec::with_capacity(self.nodes.len());
         let mut done = 0;
         let mut none = 0; // no predecessors
-        for (index, node) in self
-            .nodes
-            .iter()
-            .enumerate()
-            .map(|(i, node)| (NodeId::new(i), node))
-        {
+        for (index, node) in self.flow_nodes() {
             let mut predecessors: Vec<_> = node
                 .connected_inputs
                 .values()
@@ -240,14 +970,17 @@ where
             debug_assert!(done <= none);
             let index = candidates[done].0;
             if !candidates[done].1.is_empty() {
-                return Err(Cycle { node: index });
+                let cycle = self.find_cycles(1).pop();
+                debug_assert!(cycle.is_some());
+                return Err(cycle.unwrap_or(Cycle { path: vec![index] }));
             }
             // Remove index from remaining predecessors
             #[allow(clippy::mut_range_bound)]
             for i in none..candidates.len() {
                 let candidate = &mut candidates[i];
                 if let Ok(index) = candidate.1.binary_search(&index) {
-                    candidate.1.swap_remove(index);
+                    // Preserve the ordering for subsequent binary searches
+                    candidate.1.remove(index);
                     if candidate.1.is_empty() {
                         none += 1;
                         if none < candidates.len() {
@@ -261,40 +994,667 @@ where
         Ok(candidates[0..none].iter().map(|(node, _)| *node).collect())
     }
 
+    /// Group all nodes into topological layers
+    ///
+    /// Nodes in the first layer have no predecessors. All
+    /// predecessors of a node are located in preceding layers,
+    /// i.e. the layer of a node is the length of the longest
+    /// path from any source. Nodes within the same layer are
+    /// never connected with each other.
+    ///
+    /// Concatenating all layers results in a topological ordering.
+    pub fn topological_layers(&self) -> Result<Vec<Vec<NodeId>>, Cycle> {
+        let topo_nodes = self.topological_nodes()?;
+        let mut layer_indexes = vec![0; self.nodes.len()];
+        let mut layers: Vec<Vec<NodeId>> = Vec::new();
+        for node_id in topo_nodes {
+            let layer_index = self
+                .flow_node(node_id)
+                .connected_inputs
+                .values()
+                .map(|output| layer_indexes[usize::from(output.node_id)] + 1)
+                .max()
+                .unwrap_or(0);
+            layer_indexes[usize::from(node_id)] = layer_index;
+            if layers.len() <= layer_index {
+                layers.resize_with(layer_index + 1, Vec::new);
+            }
+            layers[layer_index].push(node_id);
+        }
+        Ok(layers)
+    }
+
+    /// Check that the flow graph is acyclic
+    ///
+    /// Reports all cycles that don't share any nodes with each
+    /// other. Cycles that overlap with an already reported cycle
+    /// are omitted.
+    pub fn validate(&self) -> Result<(), Vec<Cycle>> {
+        let cycles = self.find_cycles(self.nodes.len());
+        if cycles.is_empty() {
+            Ok(())
+        } else {
+            Err(cycles)
+        }
+    }
+
+    /// Find up to `max_cycles` cycles that are disjoint
+    fn find_cycles(&self, max_cycles: usize) -> Vec<Cycle> {
+        let mut remaining: Vec<_> = self.nodes.iter().map(Option::is_some).collect();
+        let mut cycles = Vec::new();
+        while cycles.len() < max_cycles {
+            // Exclude all nodes that could not be part of a cycle,
+            // i.e. that have no remaining predecessors
+            let mut pruned = true;
+            while pruned {
+                pruned = false;
+                for (node_id, flow_node) in self.flow_nodes() {
+                    let index = usize::from(node_id);
+                    if remaining[index]
+                        && !flow_node
+                            .connected_inputs
+                            .values()
+                            .any(|output| remaining[usize::from(output.node_id)])
+                    {
+                        remaining[index] = false;
+                        pruned = true;
+                    }
+                }
+            }
+            // Every remaining node has at least one remaining
+            // predecessor. Following those predecessors will
+            // eventually lead to a node that has already been
+            // visited.
+            let mut node_id = if let Some(index) = remaining.iter().position(|r| *r) {
+                NodeId::new(index)
+            } else {
+                break;
+            };
+            let mut path = Vec::new();
+            let start = loop {
+                if let Some(start) = path.iter().position(|visited| *visited == node_id) {
+                    break start;
+                }
+                path.push(node_id);
+                node_id = self
+                    .flow_node(node_id)
+                    .connected_inputs
+                    .values()
+                    .map(|output| output.node_id)
+                    .filter(|node_id| remaining[usize::from(*node_id)])
+                    .min()
+                    .expect("remaining predecessor");
+            };
+            path.drain(..start);
+            // Predecessors have been visited in reverse direction
+            path.reverse();
+            for node_id in &path {
+                remaining[usize::from(*node_id)] = false;
+            }
+            let first = path[0];
+            path.push(first);
+            cycles.push(Cycle { path });
+        }
+        cycles
+    }
+
+    /// Check if a sequence of nodes is a valid topological
+    /// ordering of the flow graph
+    ///
+    /// The sequence must contain every node exactly once and all
+    /// connections must lead from an earlier to a later node.
+    /// Useful for verifying a previously computed ordering after
+    /// the flow graph might have been modified.
+    pub fn is_valid_topological_order(&self, order: &[NodeId]) -> bool {
+        if order.len() != self.node_ids().count() {
+            return false;
+        }
+        let mut positions = vec![None; self.nodes.len()];
+        for (position, node_id) in order.iter().enumerate() {
+            if !self.contains_node(*node_id) {
+                return false;
+            }
+            let index = usize::from(*node_id);
+            if positions[index].is_some() {
+                return false;
+            }
+            positions[index] = Some(position);
+        }
+        self.flow_nodes().all(|(node_id, flow_node)| {
+            flow_node.connected_outputs.values().all(|input| {
+                positions[usize::from(node_id)] < positions[usize::from(input.node_id)]
+            })
+        })
+    }
+
+    /// Check if the topological ordering of all nodes has
+    /// already been computed and is still valid
+    ///
+    /// The cached ordering is discarded by every structural
+    /// modification of the flow graph, i.e. when adding nodes
+    /// or when connecting and disconnecting ports. It will be
+    /// recomputed on demand.
+    pub fn is_topology_cached(&self) -> bool {
+        self.topological_order.is_some()
+    }
+
+    /// The (cached) topological ordering of all nodes in the
+    /// flow graph
+    ///
+    /// See also: `topological_nodes()`
+    pub fn topological_order(&mut self) -> Result<&[NodeId], Cycle> {
+        if self.topological_order.is_none() {
+            self.topological_order = Some(self.topological_nodes()?);
+        }
+        Ok(self.topological_order.as_ref().unwrap())
+    }
+
+    /// Discard the cached topological ordering
+    ///
+    /// All structural modifications of the flow graph already
+    /// discard the cached ordering implicitly. Explicitly invalidating
+    /// the ordering is only needed to enforce that it is recomputed
+    /// on demand.
+    pub fn invalidate_topology(&mut self) {
+        self.topological_order = None;
+    }
+
+    /// The number of connected inputs of a node
+    pub fn in_degree(&self, node_id: NodeId) -> usize {
+        self.flow_node(node_id).connected_inputs.len()
+    }
+
+    /// The number of connected outputs of a node
+    pub fn out_degree(&self, node_id: NodeId) -> usize {
+        self.flow_node(node_id).connected_outputs.len()
+    }
+
+    /// The in-degree and out-degree of all nodes
+    ///
+    /// Returns a tuple `(node_id, in_degree, out_degree)` for each
+    /// node, ordered by node identifier.
+    ///
+    /// See also: `in_degree()`, `out_degree()`
+    pub fn degree_sequence(&self) -> Vec<(NodeId, usize, usize)> {
+        self.flow_nodes()
+            .map(|(node_id, flow_node)| {
+                (
+                    node_id,
+                    flow_node.connected_inputs.len(),
+                    flow_node.connected_outputs.len(),
+                )
+            })
+            .collect()
+    }
+
+    /// All connections of the flow graph as pairs of output
+    /// and input sockets in no particular order
+    pub fn connections(&self) -> impl Iterator<Item = (Socket, Socket)> + '_ {
+        self.flow_nodes().flat_map(|(node_id, flow_node)| {
+            flow_node
+                .connected_outputs
+                .iter()
+                .map(move |(port_index, input)| {
+                    (
+                        Socket {
+                            node_id,
+                            port_index: *port_index,
+                        },
+                        *input,
+                    )
+                })
+        })
+    }
+
+    /// The connected inputs of a node together with the output
+    /// sockets of the preceding nodes in no particular order
+    pub fn inputs_of(&self, node_id: NodeId) -> impl Iterator<Item = (PortIndex, Socket)> + '_ {
+        self.flow_node(node_id)
+            .connected_inputs
+            .iter()
+            .map(|(port_index, output)| (*port_index, *output))
+    }
+
+    /// The connected outputs of a node together with the input
+    /// sockets of the subsequent nodes in no particular order
+    pub fn outputs_of(&self, node_id: NodeId) -> impl Iterator<Item = (PortIndex, Socket)> + '_ {
+        self.flow_node(node_id)
+            .connected_outputs
+            .iter()
+            .map(|(port_index, input)| (*port_index, *input))
+    }
+
+    /// All nodes without any connected inputs in ascending order
+    pub fn source_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.flow_nodes()
+            .filter(|(_, flow_node)| flow_node.connected_inputs.is_empty())
+            .map(|(node_id, _)| node_id)
+    }
+
+    /// All nodes without any connected outputs in ascending order
+    pub fn sink_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.flow_nodes()
+            .filter(|(_, flow_node)| flow_node.connected_outputs.is_empty())
+            .map(|(node_id, _)| node_id)
+    }
+
+    /// Collect all nodes of the weakly-connected component
+    /// that contains the given node
+    ///
+    /// The returned node identifiers are sorted in ascending
+    /// order and include the given node.
+    pub fn connected_component(&self, node_id: NodeId) -> Vec<NodeId> {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut pending = vec![node_id];
+        visited[usize::from(node_id)] = true;
+        let mut component = Vec::new();
+        while let Some(node_id) = pending.pop() {
+            component.push(node_id);
+            let flow_node = self.flow_node(node_id);
+            for socket in flow_node
+                .connected_inputs
+                .values()
+                .chain(flow_node.connected_outputs.values())
+            {
+                let index = usize::from(socket.node_id);
+                if !visited[index] {
+                    visited[index] = true;
+                    pending.push(socket.node_id);
+                }
+            }
+        }
+        component.sort_unstable();
+        component
+    }
+
+    /// Extract a single component into a separate flow graph
+    ///
+    /// The new flow graph contains copies of all nodes of the
+    /// weakly-connected component that contains the representative
+    /// node together with their connections. Nodes are assigned new
+    /// ids in ascending order of their original ids.
+    ///
+    /// Edge probes are not copied.
+    pub fn extract_component(&self, representative: NodeId) -> Self
+    where
+        N: Clone,
+    {
+        let component = self.connected_component(representative);
+        let remap_socket = |socket: &Socket| {
+            let index = component
+                .binary_search(&socket.node_id)
+                .expect("connected node within component");
+            Socket {
+                node_id: NodeId::new(index),
+                port_index: socket.port_index,
+            }
+        };
+        let mut flow = Self::with_capacity(component.len());
+        for node_id in component.iter() {
+            let FlowNode {
+                node,
+                connected_inputs,
+                connected_outputs,
+            } = self.flow_node(*node_id);
+            flow.nodes.push(Some(FlowNode {
+                node: node.clone(),
+                connected_inputs: connected_inputs
+                    .iter()
+                    .map(|(port_index, socket)| (*port_index, remap_socket(socket)))
+                    .collect(),
+                connected_outputs: connected_outputs
+                    .iter()
+                    .map(|(port_index, socket)| (*port_index, remap_socket(socket)))
+                    .collect(),
+            }));
+        }
+        flow
+    }
+
+    /// Find all nodes that don't contribute to any sink
+    ///
+    /// Sinks are nodes without any outputs. A node is considered
+    /// dead if none of the sinks could be reached by following
+    /// the connections of its outputs. Dead nodes will never be
+    /// activated during the backward pass.
+    ///
+    /// Returns the ids of all dead nodes in ascending order.
+    pub fn dead_nodes(&self) -> Vec<NodeId> {
+        // Removed nodes are neither dead nor alive
+        let mut alive: Vec<_> = self.nodes.iter().map(Option::is_none).collect();
+        let mut pending = Vec::new();
+        for (node_id, flow_node) in self.flow_nodes() {
+            if flow_node.node.num_outputs() == 0 {
+                alive[usize::from(node_id)] = true;
+                pending.push(node_id);
+            }
+        }
+        while let Some(node_id) = pending.pop() {
+            for socket in self.flow_node(node_id).connected_inputs.values() {
+                let index = usize::from(socket.node_id);
+                if !alive[index] {
+                    alive[index] = true;
+                    pending.push(socket.node_id);
+                }
+            }
+        }
+        alive
+            .into_iter()
+            .enumerate()
+            .filter(|(_, alive)| !alive)
+            .map(|(index, _)| NodeId::new(index))
+            .collect()
+    }
+
+    /// Execute both passes for a single component of the flow graph
+    ///
+    /// Only the nodes of the weakly-connected component that contains
+    /// the representative node are processed, first backward and then
+    /// forward in topological order. All other nodes remain untouched.
+    pub fn process_component(&mut self, representative: NodeId) -> Result<(), Cycle> {
+        let component = self.connected_component(representative);
+        let topo_nodes: Vec<_> = self
+            .topological_order()?
+            .iter()
+            .copied()
+            .filter(|node_id| component.binary_search(node_id).is_ok())
+            .collect();
+        for node_id in topo_nodes.iter().rev() {
+            self.process_outputs(*node_id);
+        }
+        for node_id in topo_nodes.iter() {
+            self.process_inputs(*node_id);
+        }
+        Ok(())
+    }
+
+    /// Execute both passes for all nodes
+    ///
+    /// All nodes are processed backward in reverse topological
+    /// order and then forward in topological order.
+    ///
+    /// Returns the nodes that passed on values to subsequent
+    /// nodes during the forward pass in topological order.
+    pub fn process_all(&mut self) -> Result<Vec<NodeId>, Cycle> {
+        self.process_backward_all()?;
+        self.process_forward_all()
+    }
+
+    /// Execute both passes for all nodes
+    ///
+    /// The topological ordering is cached and only recomputed after
+    /// the structure of the flow graph has been modified.
+    ///
+    /// See also: `process_all()`
+    pub fn execute(&mut self) -> Result<(), Cycle> {
+        self.process_all().map(|_| ())
+    }
+
+    /// Execute only the backward pass for all nodes
+    ///
+    /// All nodes are processed in reverse topological order.
+    pub fn process_backward_all(&mut self) -> Result<(), Cycle> {
+        self.with_topological_order(|flow, topo_nodes| {
+            for node_id in topo_nodes.iter().rev() {
+                flow.process_outputs(*node_id);
+            }
+        })
+    }
+
+    /// Execute only the forward pass for all nodes
+    ///
+    /// All nodes are processed in topological order.
+    ///
+    /// Returns the nodes that passed on values to subsequent
+    /// nodes in topological order.
+    pub fn process_forward_all(&mut self) -> Result<Vec<NodeId>, Cycle> {
+        self.with_topological_order(|flow, topo_nodes| {
+            topo_nodes
+                .iter()
+                .copied()
+                .filter(|node_id| flow.process_inputs(*node_id))
+                .collect()
+        })
+    }
+
+    /// Mark a node as dirty after injecting new input values
+    ///
+    /// Dirty nodes are processed during the next incremental
+    /// execution.
+    ///
+    /// Panics if the node has been removed.
+    ///
+    /// See also: `execute_incremental()`
+    pub fn mark_dirty(&mut self, node_id: NodeId) {
+        assert!(self.contains_node(node_id), "node has been removed");
+        self.dirty_nodes.insert(node_id);
+    }
+
+    /// Mark all nodes as dirty, e.g. before the first incremental
+    /// execution
+    pub fn mark_all_dirty(&mut self) {
+        let node_ids: Vec<_> = self.node_ids().collect();
+        self.dirty_nodes.extend(node_ids);
+    }
+
+    /// Check if a node will be processed during the next
+    /// incremental execution
+    pub fn is_dirty(&self, node_id: NodeId) -> bool {
+        self.dirty_nodes.contains(&node_id)
+    }
+
+    /// Execute both passes, but only process dirty nodes during
+    /// the forward pass
+    ///
+    /// The backward pass is global and processes all nodes. The
+    /// forward pass only processes dirty nodes in topological order.
+    /// Successors of a dirty node become dirty when they receive a
+    /// value from it. All other nodes are skipped and retain their
+    /// state from the preceding execution.
+    ///
+    /// The values that have been passed on during incremental
+    /// execution are cached per output. Dirty nodes receive the
+    /// cached values of their skipped predecessors, e.g. a node that
+    /// joins multiple inputs still sees the unchanged values. The
+    /// cache is only filled by incremental execution and values of
+    /// disconnected outputs are discarded. All nodes should be marked
+    /// as dirty before the first incremental execution.
+    ///
+    /// No node is dirty afterwards.
+    ///
+    /// See also: `mark_dirty()`, `mark_all_dirty()`, `execute()`
+    pub fn execute_incremental(&mut self) -> Result<(), Cycle>
+    where
+        P: Clone,
+    {
+        self.process_backward_all()?;
+        let mut dirty_nodes = std::mem::take(&mut self.dirty_nodes);
+        self.with_topological_order(|flow, topo_nodes| {
+            for node_id in topo_nodes {
+                if !dirty_nodes.contains(node_id) {
+                    continue;
+                }
+                flow.replay_cached_inputs(*node_id, &dirty_nodes);
+                flow.forget_cached_outputs(*node_id);
+                let mut dispatched = Vec::new();
+                flow.process_inputs_with(*node_id, |output, input, payload| {
+                    dirty_nodes.insert(input.node_id);
+                    dispatched.push((output, payload.clone()));
+                });
+                flow.cached_outputs.extend(dispatched);
+            }
+        })?;
+        debug_assert!(self.dirty_nodes.is_empty());
+        Ok(())
+    }
+
+    /// Execute both passes repeatedly and collect the values
+    /// that are received by a sink
+    ///
+    /// Returns the input values of the sink for each pass, indexed
+    /// by input port. Inputs that didn't receive a value during a
+    /// pass are `None`. Only inputs that are connected could receive
+    /// values.
+    ///
+    /// Values are captured by temporarily installed edge probes.
+    /// Existing edge probes on the connections of the sink are still
+    /// invoked and will be restored afterwards.
+    pub fn run_collect(&mut self, passes: usize, sink: NodeId) -> Result<Vec<Vec<Option<P>>>, Cycle>
+    where
+        P: Clone + 'static,
+    {
+        // Fail early before installing any probes
+        self.topological_order()?;
+        let num_inputs = self.node(sink).num_inputs();
+        let values = Rc::new(RefCell::new(vec![None; num_inputs]));
+        let connected_inputs: Vec<_> = self
+            .flow_node(sink)
+            .connected_inputs
+            .iter()
+            .map(|(port_index, output)| (*port_index, *output))
+            .collect();
+        let mut previous_probes = Vec::with_capacity(connected_inputs.len());
+        for (port_index, output) in connected_inputs {
+            let previous_probe = self
+                .remove_edge_probe(output)
+                .map(|probe| Rc::new(RefCell::new(probe)));
+            let probe: EdgeProbeFn<P> = {
+                let values = Rc::clone(&values);
+                let previous_probe = previous_probe.clone();
+                Box::new(move |payload| {
+                    values.borrow_mut()[usize::from(port_index)] = Some(payload.clone());
+                    if let Some(previous_probe) = &previous_probe {
+                        (previous_probe.borrow_mut())(payload);
+                    }
+                })
+            };
+            self.set_edge_probe(output, probe);
+            previous_probes.push((output, previous_probe));
+        }
+        let mut collected = Vec::with_capacity(passes);
+        for _ in 0..passes {
+            for value in values.borrow_mut().iter_mut() {
+                *value = None;
+            }
+            self.process_all()?;
+            collected.push(values.borrow().clone());
+        }
+        for (output, previous_probe) in previous_probes {
+            // Drop the temporary probe that shares the previous probe
+            self.remove_edge_probe(output);
+            if let Some(previous_probe) = previous_probe {
+                let previous_probe = Rc::try_unwrap(previous_probe)
+                    .ok()
+                    .expect("no other references")
+                    .into_inner();
+                self.set_edge_probe(output, previous_probe);
+            }
+        }
+        Ok(collected)
+    }
+
+    /// Compile the sequence of steps for executing both passes
+    /// for all nodes without actually executing them
+    ///
+    /// The plan contains all nodes in reverse topological order
+    /// for the backward pass followed by all nodes in topological
+    /// order for the forward pass.
+    ///
+    /// See also: `run_plan()`
+    pub fn execution_plan(&self) -> Result<Vec<(Phase, NodeId)>, Cycle> {
+        let topo_nodes = if let Some(topo_nodes) = &self.topological_order {
+            topo_nodes.clone()
+        } else {
+            self.topological_nodes()?
+        };
+        let backward = topo_nodes
+            .iter()
+            .rev()
+            .map(|node_id| (Phase::Backward, *node_id));
+        let forward = topo_nodes.iter().map(|node_id| (Phase::Forward, *node_id));
+        Ok(backward.chain(forward).collect())
+    }
+
+    /// Execute a precomputed sequence of steps
+    ///
+    /// See also: `execution_plan()`
+    pub fn run_plan(&mut self, plan: &[(Phase, NodeId)]) {
+        for (phase, node_id) in plan {
+            match phase {
+                Phase::Backward => self.process_outputs(*node_id),
+                Phase::Forward => {
+                    self.process_inputs(*node_id);
+                }
+            }
+        }
+    }
+
+    /// Borrow the cached topological order while modifying
+    /// the flow graph
+    ///
+    /// The structure of the flow graph must not be modified!
+    fn with_topological_order<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self, &[NodeId]) -> R,
+    ) -> Result<R, Cycle> {
+        self.topological_order()?;
+        let topo_nodes = self.topological_order.take().unwrap_or_default();
+        let result = f(self, &topo_nodes);
+        debug_assert!(self.topological_order.is_none());
+        self.topological_order = Some(topo_nodes);
+        Ok(result)
+    }
+
+    pub(crate) fn start_recording_process_events(&mut self) {
+        self.process_events = Some(Vec::new());
+    }
+
+    pub(crate) fn stop_recording_process_events(&mut self) -> Vec<ProcessEvent> {
+        self.process_events.take().unwrap_or_default()
+    }
+
+    fn record_process_event(&mut self, node_id: NodeId, phase: Phase) {
+        if let Some(process_events) = &mut self.process_events {
+            process_events.push(ProcessEvent { node_id, phase });
+        }
+    }
+
     /// Execute backward pass for a single node
     ///
     /// Propagate the control messages from the outputs of
     /// a selected node to its inputs and then along the
     /// input connections to all outputs of preceding nodes.
+    ///
+    /// Panics if the node is connected to itself.
     pub fn process_outputs(&mut self, node_id: NodeId) {
-        let flow_node_ptr = {
-            let flow_node = self.flow_node_mut(node_id);
-            flow_node.node.process_outputs(AccessToken::new());
-            flow_node as *mut FlowNode<N>
-        };
-        // The 2nd mutable borrow is safe, because both nodes
-        // are guaranteed to be disjunct and the flow graph
-        // itself is not modified.
-        #[allow(unused_unsafe)]
-        unsafe {
-            let node = &mut (*flow_node_ptr).node;
-            for (input_index, incoming) in &(*flow_node_ptr).connected_inputs {
-                let packet = node.try_dispatch_input_packet(AccessToken::new(), *input_index);
-                if let Some(packet) = packet {
-                    let Socket {
-                        node_id: predecessor_node_id,
-                        port_index: predecessor_port_index,
-                    } = *incoming;
-                    debug_assert_ne!(node_id, predecessor_node_id); // disjunct nodes!
-                    let predecessor_node = &mut self.flow_node_mut(predecessor_node_id).node;
-                    predecessor_node.accept_output_packet(
-                        AccessToken::new(),
-                        predecessor_port_index,
-                        packet,
-                    );
-                }
+        self.record_process_event(node_id, Phase::Backward);
+        let flow_node = self.flow_node_mut(node_id);
+        flow_node.node.process_outputs(AccessToken::new());
+        // Temporarily move the connections out of the node
+        // while borrowing the node together with its peers
+        let connected_inputs = std::mem::take(&mut flow_node.connected_inputs);
+        for (input_index, incoming) in &connected_inputs {
+            let Socket {
+                node_id: predecessor_node_id,
+                port_index: predecessor_port_index,
+            } = *incoming;
+            let (flow_node, predecessor_flow_node) = disjoint_pair_mut(
+                &mut self.nodes,
+                usize::from(node_id),
+                usize::from(predecessor_node_id),
+            );
+            let packet = flow_node
+                .node
+                .try_dispatch_input_packet(AccessToken::new(), *input_index);
+            if let Some(packet) = packet {
+                predecessor_flow_node.node.accept_output_packet(
+                    AccessToken::new(),
+                    predecessor_port_index,
+                    packet,
+                );
             }
         }
+        self.flow_node_mut(node_id).connected_inputs = connected_inputs;
     }
 
     /// Execute forward pass for a single node
@@ -302,34 +1662,1061 @@ where
     /// Update the outputs of a selected node by processing the
     /// inputs and then pass those values along the output
     /// connections to all inputs of subsequent nodes.
-    pub fn process_inputs(&mut self, node_id: NodeId) {
-        let flow_node_ptr = {
-            let flow_node_ptr = self.flow_node_mut(node_id);
-            flow_node_ptr.node.process_inputs(AccessToken::new());
-            flow_node_ptr as *mut FlowNode<N>
-        };
-        // The 2nd mutable borrow is safe, because both nodes
-        // are guaranteed to be disjunct and the flow graph
-        // itself is not modified.
-        #[allow(unused_unsafe)]
-        unsafe {
-            let node = &mut (*flow_node_ptr).node;
-            for (output_index, outgoing) in &(*flow_node_ptr).connected_outputs {
-                let packet = node.try_dispatch_output_packet(AccessToken::new(), *output_index);
-                if let Some(packet) = packet {
-                    let Socket {
-                        node_id: successor_node_id,
-                        port_index: successor_port_index,
-                    } = *outgoing;
-                    debug_assert_ne!(node_id, successor_node_id); // disjunct nodes!
-                    let successor_node = &mut self.flow_node_mut(successor_node_id).node;
-                    successor_node.accept_input_packet(
-                        AccessToken::new(),
-                        successor_port_index,
-                        packet,
-                    );
+    ///
+    /// Returns `true` if at least one value has been passed on
+    /// to a subsequent node.
+    ///
+    /// Panics if the node is connected to itself.
+    pub fn process_inputs(&mut self, node_id: NodeId) -> bool {
+        self.process_inputs_with(node_id, |_, _, _| ())
+    }
+
+    /// Pass the cached values of skipped predecessors on to
+    /// the inputs of a dirty node
+    fn replay_cached_inputs(&mut self, node_id: NodeId, dirty_nodes: &HashSet<NodeId>)
+    where
+        P: Clone,
+    {
+        if self.cached_outputs.is_empty() {
+            return;
+        }
+        let packets: Vec<_> = self
+            .flow_node(node_id)
+            .connected_inputs
+            .iter()
+            .filter(|(_, output)| !dirty_nodes.contains(&output.node_id))
+            .filter_map(|(input_index, output)| {
+                self.cached_outputs
+                    .get(output)
+                    .map(|payload| (*input_index, Packet::new(payload.clone())))
+            })
+            .collect();
+        let node = &mut self.flow_node_mut(node_id).node;
+        for (input_index, packet) in packets {
+            node.accept_input_packet(AccessToken::new(), input_index, packet);
+        }
+    }
+
+    /// Discard the cached values of all outputs of a node
+    fn forget_cached_outputs(&mut self, node_id: NodeId) {
+        if self.cached_outputs.is_empty() {
+            return;
+        }
+        let cached_outputs = &mut self.cached_outputs;
+        for output_index in self.nodes[usize::from(node_id)]
+            .as_ref()
+            .expect("node has not been removed")
+            .connected_outputs
+            .keys()
+        {
+            cached_outputs.remove(&Socket {
+                node_id,
+                port_index: *output_index,
+            });
+        }
+    }
+
+    /// Execute forward pass for a single node and notify about
+    /// all values that have been passed on to successors
+    fn process_inputs_with(
+        &mut self,
+        node_id: NodeId,
+        mut on_dispatched: impl FnMut(Socket, Socket, &P),
+    ) -> bool {
+        self.record_process_event(node_id, Phase::Forward);
+        let flow_node = self.flow_node_mut(node_id);
+        flow_node.node.process_inputs(AccessToken::new());
+        // Temporarily move the connections out of the node
+        // while borrowing the node together with its peers
+        let connected_outputs = std::mem::take(&mut flow_node.connected_outputs);
+        let mut dispatched = false;
+        for (output_index, outgoing) in &connected_outputs {
+            let Socket {
+                node_id: successor_node_id,
+                port_index: successor_port_index,
+            } = *outgoing;
+            let (flow_node, successor_flow_node) = disjoint_pair_mut(
+                &mut self.nodes,
+                usize::from(node_id),
+                usize::from(successor_node_id),
+            );
+            let packet = flow_node
+                .node
+                .try_dispatch_output_packet(AccessToken::new(), *output_index);
+            if let Some(packet) = packet {
+                let output = Socket {
+                    node_id,
+                    port_index: *output_index,
+                };
+                if !self.edge_probes.is_empty() {
+                    if let Some(EdgeProbe(probe)) = self.edge_probes.get_mut(&output) {
+                        probe(&packet.payload);
+                    }
                 }
+                on_dispatched(output, *outgoing, &packet.payload);
+                successor_flow_node.node.accept_input_packet(
+                    AccessToken::new(),
+                    successor_port_index,
+                    packet,
+                );
+                dispatched = true;
+            }
+        }
+        self.flow_node_mut(node_id).connected_outputs = connected_outputs;
+        dispatched
+    }
+}
+
+/// Borrow two different nodes mutably at the same time
+///
+/// Panics if both indexes are equal or if any of the nodes
+/// has been removed.
+fn disjoint_pair_mut<T>(
+    nodes: &mut [Option<T>],
+    index: usize,
+    peer_index: usize,
+) -> (&mut T, &mut T) {
+    assert_ne!(index, peer_index, "node is connected to itself");
+    let (node, peer_node) = if index < peer_index {
+        let (head, tail) = nodes.split_at_mut(peer_index);
+        (&mut head[index], &mut tail[0])
+    } else {
+        let (head, tail) = nodes.split_at_mut(index);
+        (&mut tail[0], &mut head[peer_index])
+    };
+    (
+        node.as_mut().expect("node has not been removed"),
+        peer_node.as_mut().expect("node has not been removed"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::fixtures::*;
+
+    type SplitterFlow = Flow<OneToManySplitter<(), i32>, (), i32>;
+
+    /// Two separate chains: a -> b and c -> d
+    fn two_chains() -> (SplitterFlow, [NodeId; 4]) {
+        let mut flow = Flow::new();
+        let a = flow.add_node(OneToManySplitter::new(1));
+        let b = flow.add_node(OneToManySplitter::new(1));
+        let c = flow.add_node(OneToManySplitter::new(1));
+        let d = flow.add_node(OneToManySplitter::new(1));
+        flow.connect(socket(a, 0), socket(b, 0));
+        flow.connect(socket(c, 0), socket(d, 0));
+        (flow, [a, b, c, d])
+    }
+
+    #[test]
+    fn connected_component() {
+        let (flow, [a, b, c, d]) = two_chains();
+        assert_eq!(vec![a, b], flow.connected_component(a));
+        assert_eq!(vec![a, b], flow.connected_component(b));
+        assert_eq!(vec![c, d], flow.connected_component(d));
+    }
+
+    #[test]
+    fn process_component_in_isolation() {
+        let (mut flow, [a, b, c, d]) = two_chains();
+        flow.node_mut(b).request_output(PortIndex::new(0), ());
+        flow.node_mut(d).request_output(PortIndex::new(0), ());
+        flow.node_mut(a).input_mut().incoming = Some(1);
+        flow.node_mut(c).input_mut().incoming = Some(2);
+        flow.process_component(b).unwrap();
+        assert_eq!(Some(1), flow.node(b).output(PortIndex::new(0)).outgoing);
+        // The other component remains untouched
+        assert_eq!(None, flow.node(d).output(PortIndex::new(0)).outgoing);
+        assert!(!flow.node(c).is_input_requested());
+        assert_eq!(Some(2), flow.node(c).input().incoming);
+    }
+
+    #[test]
+    fn extract_component_with_new_ids() {
+        let (mut flow, [_, _, c, d]) = two_chains();
+        flow.node_mut(c).input_mut().incoming = Some(7);
+        let extracted = flow.extract_component(d);
+        assert_eq!(2, extracted.node_ids().count());
+        let (c, d) = (NodeId::new(0), NodeId::new(1));
+        assert_eq!(
+            vec![(socket(c, 0), socket(d, 0))],
+            extracted.connections().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(7), extracted.node(c).input().incoming);
+        // The original flow graph is not modified
+        assert_eq!(4, flow.node_ids().count());
+    }
+
+    #[test]
+    fn find_dead_nodes() {
+        let mut flow: SplitterFlow = Flow::new();
+        let a = flow.add_node(OneToManySplitter::new(1));
+        // A sink without any outputs
+        let sink = flow.add_node(OneToManySplitter::new(0));
+        let removed = flow.add_node(OneToManySplitter::new(1));
+        let dangling = flow.add_node(OneToManySplitter::new(1));
+        let b = flow.add_node(OneToManySplitter::new(1));
+        let c = flow.add_node(OneToManySplitter::new(1));
+        flow.connect(socket(a, 0), socket(sink, 0));
+        flow.connect(socket(b, 0), socket(c, 0));
+        flow.remove_node(removed);
+        assert_eq!(vec![dangling, b, c], flow.dead_nodes());
+        flow.disconnect_output(socket(a, 0));
+        flow.connect(socket(c, 0), socket(sink, 0));
+        assert_eq!(vec![a, dangling], flow.dead_nodes());
+    }
+
+    #[test]
+    fn validate_topological_orders() {
+        let (mut flow, [a, b, c, d]) = two_chains();
+        let order = flow.topological_nodes().unwrap();
+        assert!(flow.is_valid_topological_order(&order));
+        assert!(flow.is_valid_topological_order(&[c, a, d, b]));
+        assert!(!flow.is_valid_topological_order(&[b, a, c, d]));
+        // Missing, duplicate, and unknown nodes
+        assert!(!flow.is_valid_topological_order(&[a, b, c]));
+        assert!(!flow.is_valid_topological_order(&[a, b, c, c]));
+        assert!(!flow.is_valid_topological_order(&[a, b, c, NodeId::new(4)]));
+        // Stale after modifying the flow graph
+        flow.connect(socket(d, 0), socket(a, 0));
+        assert!(!flow.is_valid_topological_order(&order));
+        assert!(flow.is_valid_topological_order(&[c, d, a, b]));
+        flow.remove_node(d);
+        assert!(!flow.is_valid_topological_order(&[c, d, a, b]));
+        assert!(flow.is_valid_topological_order(&[c, a, b]));
+    }
+
+    /// Bypass the validation of connections for creating cycles
+    fn connect_unchecked(flow: &mut SplitterFlow, output: Socket, input: Socket) {
+        flow.flow_node_mut(output.node_id)
+            .connected_outputs
+            .insert(output.port_index, input);
+        flow.flow_node_mut(input.node_id)
+            .connected_inputs
+            .insert(input.port_index, output);
+        flow.invalidate_topology();
+    }
+
+    #[test]
+    fn validate_detects_disjoint_cycles() {
+        let (mut flow, [a, b, _, _]) = two_chains();
+        assert_eq!(Ok(()), flow.validate());
+        let e = flow.add_node(OneToManySplitter::new(1));
+        connect_unchecked(&mut flow, socket(e, 0), socket(e, 0));
+        assert_eq!(Err(vec![Cycle { path: vec![e, e] }]), flow.validate());
+        connect_unchecked(&mut flow, socket(b, 0), socket(a, 0));
+        assert_eq!(
+            Err(vec![
+                Cycle {
+                    path: vec![b, a, b],
+                },
+                Cycle { path: vec![e, e] },
+            ]),
+            flow.validate()
+        );
+        assert!(flow.topological_nodes().is_err());
+    }
+
+    #[test]
+    fn grow_splitter_and_connect_new_output() {
+        let (mut flow, [a, b, c, _]) = two_chains();
+        flow.disconnect_output(socket(c, 0));
+        flow.set_node_arity(a, 1, 2);
+        assert_eq!(2, flow.node(a).num_outputs());
+        flow.connect(socket(a, 1), socket(c, 0));
+        flow.node_mut(b).request_output(PortIndex::new(0), ());
+        flow.node_mut(c).request_output(PortIndex::new(0), ());
+        flow.node_mut(a).input_mut().incoming = Some(1);
+        flow.execute().unwrap();
+        assert_eq!(Some(1), flow.node(b).output(PortIndex::new(0)).outgoing);
+        assert_eq!(Some(1), flow.node(c).output(PortIndex::new(0)).outgoing);
+        // Shrinking removes the connection of the surplus output
+        flow.set_node_arity(a, 1, 1);
+        assert_eq!(
+            vec![(socket(a, 0), socket(b, 0))],
+            flow.outputs_of(a)
+                .map(|(port_index, input)| (
+                    Socket {
+                        node_id: a,
+                        port_index
+                    },
+                    input
+                ))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(0, flow.in_degree(c));
+    }
+
+    #[test]
+    #[should_panic(expected = "The number of inputs cannot be changed")]
+    fn resize_fixed_inputs() {
+        let (mut flow, [a, _, _, _]) = two_chains();
+        flow.set_node_arity(a, 2, 1);
+    }
+
+    #[test]
+    fn map_nodes_into_boxed_nodes() {
+        let (mut flow, [a, b, c, d]) = two_chains();
+        let order = flow.topological_order().unwrap().to_vec();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let probe_seen = Rc::clone(&seen);
+        flow.set_edge_probe(
+            socket(a, 0),
+            Box::new(move |value| probe_seen.borrow_mut().push(*value)),
+        );
+        flow.node_mut(b).request_output(PortIndex::new(0), ());
+        flow.node_mut(a).input_mut().incoming = Some(1);
+        let connections: Vec<_> = flow.connections().collect();
+        let mut flow = flow.map_nodes(|node| Box::new(node) as Box<dyn Node<(), i32>>);
+        assert_eq!(connections, flow.connections().collect::<Vec<_>>());
+        assert!(flow.is_topology_cached());
+        assert_eq!(order, flow.topological_order().unwrap());
+        flow.execute().unwrap();
+        assert_eq!(vec![1], *seen.borrow());
+        assert_eq!(vec![c, d], flow.connected_component(c));
+    }
+
+    #[test]
+    fn edge_probe_observes_forwarded_values() {
+        let (mut flow, [a, b, _, _]) = two_chains();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let probe_seen = Rc::clone(&seen);
+        flow.set_edge_probe(
+            socket(a, 0),
+            Box::new(move |value| probe_seen.borrow_mut().push(*value)),
+        );
+        for value in 0..3 {
+            flow.node_mut(b).request_output(PortIndex::new(0), ());
+            flow.node_mut(a).input_mut().incoming = Some(value);
+            flow.process_component(a).unwrap();
+        }
+        assert_eq!(vec![0, 1, 2], *seen.borrow());
+        // No more values after removing the probe
+        assert!(flow.remove_edge_probe(socket(a, 0)).is_some());
+        flow.node_mut(b).request_output(PortIndex::new(0), ());
+        flow.node_mut(a).input_mut().incoming = Some(3);
+        flow.process_component(a).unwrap();
+        assert_eq!(vec![0, 1, 2], *seen.borrow());
+    }
+
+    #[test]
+    fn edge_probe_ignores_inactive_connections() {
+        let (mut flow, [a, b, _, _]) = two_chains();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let probe_seen = Rc::clone(&seen);
+        flow.set_edge_probe(
+            socket(a, 0),
+            Box::new(move |value| probe_seen.borrow_mut().push(*value)),
+        );
+        // The output of b is not requested
+        flow.node_mut(a).input_mut().incoming = Some(1);
+        flow.process_component(b).unwrap();
+        assert!(seen.borrow().is_empty());
+    }
+
+    #[test]
+    fn topological_order_is_cached_until_modified() {
+        let mut flow: SplitterFlow = Flow::new();
+        let a = flow.add_node(OneToManySplitter::new(1));
+        assert!(!flow.is_topology_cached());
+        flow.topological_order().unwrap();
+        assert!(flow.is_topology_cached());
+        let b = flow.add_node(OneToManySplitter::new(1));
+        assert!(!flow.is_topology_cached());
+        flow.topological_order().unwrap();
+        flow.connect(socket(a, 0), socket(b, 0));
+        assert!(!flow.is_topology_cached());
+        assert_eq!(&[a, b], flow.topological_order().unwrap());
+        // Processing doesn't modify the structure
+        flow.execute().unwrap();
+        assert!(flow.is_topology_cached());
+        flow.disconnect_input(socket(b, 0));
+        assert!(!flow.is_topology_cached());
+        flow.topological_order().unwrap();
+        flow.invalidate_topology();
+        assert!(!flow.is_topology_cached());
+    }
+
+    crate::node_enum! {
+        enum CalcTestNode: Node<(), f64> {
+            Splitter(OneToManySplitter<(), f64>),
+            Calc(CalcNode<()>),
+            Sink(RingBufferSink<(), f64>),
+        }
+    }
+
+    #[test]
+    fn process_calculator_flow() {
+        let calc = CalcNode::<()>::with_default_operations();
+        let num_outputs = calc.num_outputs();
+        let mut flow: Flow<CalcTestNode, (), f64> = Flow::new();
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(num_outputs, 4).into());
+        let splitter_id = flow.add_node(OneToManySplitter::new(2).into());
+        let calc_id = flow.add_node(calc.into());
+        for port_index in 0..2 {
+            flow.connect(socket(splitter_id, port_index), socket(calc_id, port_index));
+        }
+        for port_index in 0..num_outputs {
+            flow.connect(socket(calc_id, port_index), socket(sink_id, port_index));
+        }
+        for value in 0..4 {
+            let sink = unwrap_node!(flow.node_mut(sink_id), CalcTestNode::Sink);
+            // Only the sum and the product
+            sink.request_input(PortIndex::new(2), ());
+            sink.request_input(PortIndex::new(4), ());
+            flow.process_backward_all().unwrap();
+            let splitter = unwrap_node!(flow.node_mut(splitter_id), CalcTestNode::Splitter);
+            assert!(splitter.is_input_requested());
+            splitter.input_mut().incoming = Some(f64::from(value));
+            flow.process_forward_all().unwrap();
+        }
+        let sink = unwrap_node!(flow.node(sink_id), CalcTestNode::Sink);
+        assert_eq!(vec![0.0, 2.0, 4.0, 6.0], recent_values(sink, 2));
+        assert_eq!(vec![0.0, 1.0, 4.0, 9.0], recent_values(sink, 4));
+        assert!(recent_values(sink, 0).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "node is connected to itself")]
+    fn process_outputs_of_node_connected_to_itself() {
+        let mut flow: SplitterFlow = Flow::new();
+        let a = flow.add_node(OneToManySplitter::new(1));
+        // Bypass the validation of connections
+        flow.flow_node_mut(a)
+            .connected_inputs
+            .insert(PortIndex::new(0), socket(a, 0));
+        flow.node_mut(a).request_output(PortIndex::new(0), ());
+        flow.process_outputs(a);
+    }
+
+    #[test]
+    #[should_panic(expected = "node is connected to itself")]
+    fn process_inputs_of_node_connected_to_itself() {
+        let mut flow: SplitterFlow = Flow::new();
+        let a = flow.add_node(OneToManySplitter::new(1));
+        // Bypass the validation of connections
+        flow.flow_node_mut(a)
+            .connected_outputs
+            .insert(PortIndex::new(0), socket(a, 0));
+        flow.process_inputs(a);
+    }
+
+    #[test]
+    fn topological_nodes_with_many_predecessors() {
+        const WIDTH: usize = 8;
+        let mut flow: Flow<Box<dyn Node<(), i32>>, (), i32> = Flow::new();
+        let adder = flow.add_node(Box::new(AdderNode::<(), i32>::new(WIDTH)));
+        let splitter = flow.add_node(Box::new(OneToManySplitter::<(), i32>::new(WIDTH)));
+        for port_index in 0..WIDTH {
+            let node_id = flow.add_node(Box::new(OneToManySplitter::<(), i32>::new(1)));
+            flow.connect(socket(splitter, port_index), socket(node_id, 0));
+            // Connect in reverse order of the node ids
+            flow.connect(socket(node_id, 0), socket(adder, WIDTH - 1 - port_index));
+        }
+        let topo_nodes = flow.topological_nodes().unwrap();
+        assert_eq!(WIDTH + 2, topo_nodes.len());
+        assert_eq!(Some(&splitter), topo_nodes.first());
+        assert_eq!(Some(&adder), topo_nodes.last());
+    }
+
+    #[test]
+    fn disconnected_branches_no_longer_activate_upstream() {
+        let mut flow: SplitterFlow = Flow::new();
+        let a = flow.add_node(OneToManySplitter::new(2));
+        let b = flow.add_node(OneToManySplitter::new(1));
+        let c = flow.add_node(OneToManySplitter::new(1));
+        flow.connect(socket(a, 0), socket(b, 0));
+        flow.connect(socket(a, 1), socket(c, 0));
+        flow.node_mut(b).request_output(PortIndex::new(0), ());
+        flow.node_mut(c).request_output(PortIndex::new(0), ());
+        // Without any input values the controls are never consumed
+        flow.execute().unwrap();
+        assert!(flow.node(a).is_input_requested());
+        flow.disconnect_output(socket(a, 1));
+        assert_eq!(None, flow.node(a).output(PortIndex::new(1)).incoming);
+        flow.execute().unwrap();
+        assert!(flow.node(a).is_input_requested());
+        flow.disconnect_input(socket(b, 0));
+        assert_eq!(None, flow.node(a).output(PortIndex::new(0)).incoming);
+        flow.execute().unwrap();
+        assert!(!flow.node(a).is_input_requested());
+    }
+
+    fn forward_process_events(flow: &mut SplitterFlow) -> Vec<NodeId> {
+        flow.start_recording_process_events();
+        flow.execute_incremental().unwrap();
+        flow.stop_recording_process_events()
+            .into_iter()
+            .filter(|event| event.phase == Phase::Forward)
+            .map(|event| event.node_id)
+            .collect()
+    }
+
+    #[test]
+    fn execute_incremental_processes_only_dirty_nodes() {
+        let (mut flow, [a, b, _, d]) = two_chains();
+        flow.node_mut(b).request_output(PortIndex::new(0), ());
+        flow.node_mut(d).request_output(PortIndex::new(0), ());
+        flow.node_mut(a).input_mut().incoming = Some(1);
+        flow.mark_dirty(a);
+        assert!(flow.is_dirty(a));
+        assert!(!flow.is_dirty(b));
+        // The successor becomes dirty after receiving a value
+        assert_eq!(vec![a, b], forward_process_events(&mut flow));
+        assert!(!flow.is_dirty(a));
+        assert!(!flow.is_dirty(b));
+        assert_eq!(Some(1), flow.node(b).output(PortIndex::new(0)).outgoing);
+        assert_eq!(None, flow.node(d).output(PortIndex::new(0)).outgoing);
+        // Nothing to do without any dirty nodes
+        assert!(forward_process_events(&mut flow).is_empty());
+        flow.mark_all_dirty();
+        assert_eq!(4, forward_process_events(&mut flow).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "node has been removed")]
+    fn mark_removed_node_as_dirty() {
+        let (mut flow, [a, _, _, _]) = two_chains();
+        flow.remove_node(a);
+        flow.mark_dirty(a);
+    }
+
+    #[test]
+    fn control_sink_through_ports_of_flow() {
+        let (mut flow, [lhs_id, _, adder_id, sink_id], invocations) =
+            adder_flow(counter_source(1, 1), counter_source(10, 10));
+        let sink_input = socket(sink_id, 0);
+        for _ in 0..2 {
+            assert_eq!(
+                Some(()),
+                flow.with_input_port_mut(sink_input, |port| port.outgoing = Some(()))
+            );
+            flow.process_backward_all().unwrap();
+            assert_eq!(
+                Some(true),
+                flow.with_output_port_mut(socket(lhs_id, 0), |port| port.incoming.is_some())
+            );
+            flow.process_forward_all().unwrap();
+        }
+        assert_eq!(2, invocations.get());
+        let sink = unwrap_node!(flow.node(sink_id), AdderTestNode::Sink);
+        assert_eq!(vec![11, 22], recent_values(sink, 0));
+        // Ports that don't exist are not accessible
+        assert_eq!(None, flow.with_input_port_mut(socket(sink_id, 1), |_| ()));
+        assert_eq!(None, flow.with_output_port_mut(socket(sink_id, 0), |_| ()));
+        assert_eq!(None, flow.with_input_port_mut(socket(lhs_id, 0), |_| ()));
+        assert_eq!(
+            Some(None),
+            flow.with_output_port_mut(socket(adder_id, 0), |port| port.outgoing)
+        );
+    }
+
+    #[test]
+    fn execute_incremental_replays_values_of_skipped_nodes() {
+        let (mut flow, [lhs_id, rhs_id, adder_id, sink_id], invocations) =
+            adder_flow(counter_source(1, 1), counter_source(100, 100));
+        let execute = |flow: &mut AdderFlow| {
+            unwrap_node!(flow.node_mut(sink_id), AdderTestNode::Sink).request_all_inputs(());
+            flow.execute_incremental().unwrap();
+        };
+        flow.mark_all_dirty();
+        execute(&mut flow);
+        assert_eq!(1, invocations.get());
+        flow.mark_dirty(lhs_id);
+        execute(&mut flow);
+        flow.mark_dirty(lhs_id);
+        execute(&mut flow);
+        assert_eq!(3, invocations.get());
+        // Neither the sources nor the adder are invoked
+        execute(&mut flow);
+        assert_eq!(3, invocations.get());
+        flow.mark_dirty(rhs_id);
+        execute(&mut flow);
+        assert_eq!(4, invocations.get());
+        let sink = unwrap_node!(flow.node(sink_id), AdderTestNode::Sink);
+        assert_eq!(vec![101, 102, 103, 203], recent_values(sink, 0));
+        // Values of disconnected outputs are discarded
+        flow.disconnect_output(socket(rhs_id, 0));
+        flow.connect(socket(rhs_id, 0), socket(adder_id, 1));
+        flow.mark_dirty(lhs_id);
+        execute(&mut flow);
+        let sink = unwrap_node!(flow.node(sink_id), AdderTestNode::Sink);
+        assert_eq!(Some(&4), sink.recent(PortIndex::new(0)).back());
+    }
+
+    crate::node_enum! {
+        #[derive(Clone)]
+        enum JoinTestNode: Node<(), i32> {
+            Splitter(OneToManySplitter<(), i32>),
+            Adder(AdderNode<(), i32>),
+            Sink(RingBufferSink<(), i32>),
+        }
+    }
+
+    #[test]
+    fn connect_summing_of_two_sources() {
+        let mut flow: Flow<JoinTestNode, (), i32> = Flow::new();
+        let lhs_id = flow.add_node(OneToManySplitter::new(1).into());
+        let rhs_id = flow.add_node(OneToManySplitter::new(1).into());
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(1, 4).into());
+        let adder_id =
+            flow.connect_summing(&[socket(lhs_id, 0), socket(rhs_id, 0)], socket(sink_id, 0));
+        let mut connections: Vec<_> = flow.connections().collect();
+        connections.sort_unstable_by_key(|(output, _)| output.node_id);
+        assert_eq!(
+            vec![
+                (socket(lhs_id, 0), socket(adder_id, 0)),
+                (socket(rhs_id, 0), socket(adder_id, 1)),
+                (socket(adder_id, 0), socket(sink_id, 0)),
+            ],
+            connections
+        );
+        for (lhs, rhs) in &[(1, 2), (10, 20)] {
+            unwrap_node!(flow.node_mut(sink_id), JoinTestNode::Sink).request_all_inputs(());
+            unwrap_node!(flow.node_mut(lhs_id), JoinTestNode::Splitter)
+                .input_mut()
+                .incoming = Some(*lhs);
+            unwrap_node!(flow.node_mut(rhs_id), JoinTestNode::Splitter)
+                .input_mut()
+                .incoming = Some(*rhs);
+            flow.execute().unwrap();
+        }
+        let sink = unwrap_node!(flow.node(sink_id), JoinTestNode::Sink);
+        assert_eq!(vec![3, 30], recent_values(sink, 0));
+    }
+
+    #[test]
+    fn execute_incremental_after_restoring_snapshot() {
+        let mut flow: Flow<JoinTestNode, (), i32> = Flow::new();
+        let lhs_id = flow.add_node(OneToManySplitter::new(1).into());
+        let rhs_id = flow.add_node(OneToManySplitter::new(1).into());
+        let adder_id = flow.add_node(AdderNode::new(2).into());
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(1, 4).into());
+        flow.connect(socket(lhs_id, 0), socket(adder_id, 0));
+        flow.connect(socket(rhs_id, 0), socket(adder_id, 1));
+        flow.connect(socket(adder_id, 0), socket(sink_id, 0));
+        let execute = |flow: &mut Flow<JoinTestNode, (), i32>, values: &[(NodeId, i32)]| {
+            unwrap_node!(flow.node_mut(sink_id), JoinTestNode::Sink).request_all_inputs(());
+            for (node_id, value) in values {
+                unwrap_node!(flow.node_mut(*node_id), JoinTestNode::Splitter)
+                    .input_mut()
+                    .incoming = Some(*value);
+                flow.mark_dirty(*node_id);
+            }
+            flow.execute_incremental().unwrap();
+        };
+        // The snapshot contains the input values of both sources
+        execute(&mut flow, &[]);
+        for (node_id, value) in &[(lhs_id, 1), (rhs_id, 10)] {
+            unwrap_node!(flow.node_mut(*node_id), JoinTestNode::Splitter)
+                .input_mut()
+                .incoming = Some(*value);
+        }
+        let snapshot = flow.snapshot();
+        flow.mark_all_dirty();
+        execute(&mut flow, &[]);
+        execute(&mut flow, &[(lhs_id, 2)]);
+        flow.restore(&snapshot);
+        // All nodes are processed after restoring the snapshot
+        assert!(flow.node_ids().all(|node_id| flow.is_dirty(node_id)));
+        execute(&mut flow, &[]);
+        // The skipped source still contributes its value
+        execute(&mut flow, &[(lhs_id, 3)]);
+        // The history of the sink has been restored, too
+        let sink = unwrap_node!(flow.node(sink_id), JoinTestNode::Sink);
+        assert_eq!(vec![11, 13], recent_values(sink, 0));
+    }
+
+    crate::node_enum! {
+        enum CounterTestNode: Node<(), u64> {
+            Counter(CounterSource<(), u64>),
+            Sink(RingBufferSink<(), u64>),
+        }
+    }
+
+    #[test]
+    fn run_collect_values_of_counter() {
+        let mut flow: Flow<CounterTestNode, (), u64> = Flow::new();
+        let counter_id = flow.add_node(CounterSource::new(0, 1, None).into());
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(2, 0).into());
+        flow.connect(socket(counter_id, 0), socket(sink_id, 0));
+        unwrap_node!(flow.node_mut(sink_id), CounterTestNode::Sink).request_all_inputs(());
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let probe_seen = Rc::clone(&seen);
+        flow.set_edge_probe(
+            socket(counter_id, 0),
+            Box::new(move |value| probe_seen.borrow_mut().push(*value)),
+        );
+        let collected = flow.run_collect(5, sink_id).unwrap();
+        // The unconnected input never receives a value
+        assert_eq!(
+            (0..5)
+                .map(|value| vec![Some(value), None])
+                .collect::<Vec<_>>(),
+            collected
+        );
+        // The existing probe has been invoked and restored
+        assert_eq!(vec![0, 1, 2, 3, 4], *seen.borrow());
+        flow.process_all().unwrap();
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], *seen.borrow());
+    }
+
+    #[test]
+    fn restore_snapshot_after_processing() {
+        let (mut flow, [a, b, _, d]) = two_chains();
+        flow.node_mut(b).request_output(PortIndex::new(0), ());
+        flow.node_mut(a).input_mut().incoming = Some(1);
+        let snapshot = flow.snapshot();
+        assert_eq!(4, snapshot.num_nodes());
+        assert_eq!(Some(1), snapshot.node(a).input().incoming);
+        flow.process_component(a).unwrap();
+        let processed = flow.snapshot();
+        assert_eq!(vec![a, b], snapshot.changed_nodes(&processed));
+        assert_eq!(
+            Some(1),
+            processed.node(b).output(PortIndex::new(0)).outgoing
+        );
+        flow.restore(&snapshot);
+        assert_eq!(snapshot, flow.snapshot());
+        assert_eq!(None, flow.node(b).output(PortIndex::new(0)).outgoing);
+        assert_eq!(Some(1), flow.node(a).input().incoming);
+        // Rewinding doesn't affect subsequent passes
+        flow.process_component(a).unwrap();
+        assert_eq!(processed, flow.snapshot());
+        assert!(flow.snapshot().changed_nodes(&processed).is_empty());
+        // The other component remains untouched
+        assert_eq!(None, flow.node(d).input().incoming);
+    }
+
+    #[test]
+    fn restore_snapshot_with_different_structure() {
+        let (mut flow, [a, b, c, d]) = two_chains();
+        let snapshot = flow.snapshot();
+        flow.remove_node(d);
+        flow.disconnect_input(socket(b, 0));
+        let modified = flow.snapshot();
+        assert_eq!(3, modified.num_nodes());
+        // Both the disconnected and the removed nodes have changed
+        assert_eq!(vec![a, b, c, d], snapshot.changed_nodes(&modified));
+        flow.restore(&snapshot);
+        assert!(flow.contains_node(d));
+        assert_eq!(
+            vec![(socket(a, 0), socket(b, 0)), (socket(c, 0), socket(d, 0))],
+            flow.connections().collect::<Vec<_>>()
+        );
+        assert_eq!(snapshot, flow.snapshot());
+    }
+
+    /// Records the invocations of all lifecycle hooks
+    #[derive(Debug)]
+    struct HookRecorder {
+        input: Port<i32, ()>,
+        output: Port<(), i32>,
+        max_fan_in: Option<usize>,
+        events: Vec<(&'static str, usize)>,
+    }
+
+    impl HookRecorder {
+        fn with_max_fan_in(max_fan_in: Option<usize>) -> Self {
+            Self {
+                input: Port::new(),
+                output: Port::new(),
+                max_fan_in,
+                events: Vec::new(),
             }
         }
     }
+
+    impl Default for HookRecorder {
+        fn default() -> Self {
+            Self::with_max_fan_in(Some(1))
+        }
+    }
+
+    impl Node<(), i32> for HookRecorder {}
+
+    impl NodeInputs<(), i32> for HookRecorder {
+        fn num_inputs(&self) -> usize {
+            1
+        }
+
+        fn max_fan_in(&self, _input_index: PortIndex) -> Option<usize> {
+            self.max_fan_in
+        }
+
+        fn accept_input_packet(
+            &mut self,
+            _token: AccessToken,
+            _input_index: PortIndex,
+            packet: Packet<i32, ()>,
+        ) {
+            self.input.accept_packet(packet);
+        }
+
+        fn try_dispatch_input_packet(
+            &mut self,
+            _token: AccessToken,
+            _input_index: PortIndex,
+        ) -> Option<Packet<(), i32>> {
+            self.input.try_dispatch_packet()
+        }
+    }
+
+    impl NodeOutputs<(), i32> for HookRecorder {
+        fn num_outputs(&self) -> usize {
+            1
+        }
+
+        fn accept_output_packet(
+            &mut self,
+            _token: AccessToken,
+            _output_index: PortIndex,
+            packet: Packet<(), i32>,
+        ) {
+            self.output.accept_packet(packet);
+        }
+
+        fn try_dispatch_output_packet(
+            &mut self,
+            _token: AccessToken,
+            _output_index: PortIndex,
+        ) -> Option<Packet<i32, ()>> {
+            self.output.try_dispatch_packet()
+        }
+
+        fn reset_output_control(&mut self, _token: AccessToken, _output_index: PortIndex) {
+            self.output.incoming = None;
+        }
+    }
+
+    impl NodeProcessor for HookRecorder {
+        fn process_inputs(&mut self, _token: AccessToken) {
+            self.output.outgoing = self.input.incoming.take();
+        }
+
+        fn process_outputs(&mut self, _token: AccessToken) {
+            self.input.outgoing = self.output.incoming;
+        }
+
+        fn on_input_connected(&mut self, _token: AccessToken, input_index: PortIndex) {
+            self.events.push(("input_connected", input_index.index()));
+        }
+
+        fn on_input_disconnected(&mut self, _token: AccessToken, input_index: PortIndex) {
+            self.events
+                .push(("input_disconnected", input_index.index()));
+        }
+
+        fn on_output_connected(&mut self, _token: AccessToken, output_index: PortIndex) {
+            self.events.push(("output_connected", output_index.index()));
+        }
+
+        fn on_output_disconnected(&mut self, _token: AccessToken, output_index: PortIndex) {
+            self.events
+                .push(("output_disconnected", output_index.index()));
+        }
+    }
+
+    #[test]
+    fn reject_connections_that_exceed_fan_in() {
+        let mut flow: Flow<HookRecorder, (), i32> = Flow::new();
+        let a = flow.add_node(HookRecorder::default());
+        let b = flow.add_node(HookRecorder::default());
+        let c = flow.add_node(HookRecorder::with_max_fan_in(Some(0)));
+        assert_eq!(
+            Err(ConnectError::FanInExceeded {
+                input: socket(c, 0),
+                max_fan_in: 0,
+            }),
+            flow.try_connect(socket(a, 0), socket(c, 0))
+        );
+        flow.connect(socket(a, 0), socket(b, 0));
+        // The default input accepts only a single connection
+        // and the existing connection is not replaced
+        assert_eq!(
+            Err(ConnectError::FanInExceeded {
+                input: socket(b, 0),
+                max_fan_in: 1,
+            }),
+            flow.try_connect(socket(c, 0), socket(b, 0))
+        );
+        assert_eq!(
+            vec![(socket(a, 0), socket(b, 0))],
+            flow.connections().collect::<Vec<_>>()
+        );
+        assert!(flow.node(c).events.is_empty());
+        flow.reconnect(socket(c, 0), socket(b, 0));
+        assert_eq!(
+            vec![(socket(c, 0), socket(b, 0))],
+            flow.connections().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cap_fan_in_at_single_connection() {
+        for max_fan_in in [None, Some(2)].iter().copied() {
+            let mut flow: Flow<HookRecorder, (), i32> = Flow::new();
+            let a = flow.add_node(HookRecorder::default());
+            let b = flow.add_node(HookRecorder::default());
+            let c = flow.add_node(HookRecorder::with_max_fan_in(max_fan_in));
+            flow.connect(socket(a, 0), socket(c, 0));
+            assert_eq!(
+                Err(ConnectError::InputAlreadyConnected {
+                    input: socket(c, 0),
+                    output: socket(a, 0),
+                }),
+                flow.try_connect(socket(b, 0), socket(c, 0))
+            );
+            assert_eq!(
+                vec![(socket(a, 0), socket(c, 0))],
+                flow.connections().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn try_connect_rejects_invalid_connections() {
+        let mut flow: Flow<HookRecorder, (), i32> = Flow::new();
+        let a = flow.add_node(HookRecorder::default());
+        let b = flow.add_node(HookRecorder::default());
+        let c = flow.add_node(HookRecorder::default());
+        let d = flow.add_node(HookRecorder::default());
+        flow.remove_node(d);
+        flow.connect(socket(a, 0), socket(b, 0));
+        let topology = flow.topology();
+        assert_eq!(
+            Err(ConnectError::UnknownNode { node_id: d }),
+            flow.try_connect(socket(c, 0), socket(d, 0))
+        );
+        assert_eq!(
+            Err(ConnectError::UnknownNode {
+                node_id: NodeId::new(4),
+            }),
+            flow.try_connect(socket(NodeId::new(4), 0), socket(c, 0))
+        );
+        assert_eq!(
+            Err(ConnectError::ReflexiveConnection { node_id: c }),
+            flow.try_connect(socket(c, 0), socket(c, 0))
+        );
+        assert_eq!(
+            Err(ConnectError::PortIndexOutOfRange {
+                socket: socket(c, 1),
+                num_ports: 1,
+            }),
+            flow.try_connect(socket(c, 1), socket(a, 0))
+        );
+        assert_eq!(
+            Err(ConnectError::PortIndexOutOfRange {
+                socket: socket(a, 1),
+                num_ports: 1,
+            }),
+            flow.try_connect(socket(c, 0), socket(a, 1))
+        );
+        assert_eq!(
+            Err(ConnectError::OutputAlreadyConnected {
+                output: socket(a, 0),
+                input: socket(b, 0),
+            }),
+            flow.try_connect(socket(a, 0), socket(c, 0))
+        );
+        assert_eq!(
+            Err(ConnectError::FanInExceeded {
+                input: socket(b, 0),
+                max_fan_in: 1,
+            }),
+            flow.try_connect(socket(c, 0), socket(b, 0))
+        );
+        assert_eq!(
+            Err(ConnectError::WouldCreateCycle(Cycle {
+                path: vec![b, a, b],
+            })),
+            flow.try_connect(socket(b, 0), socket(a, 0))
+        );
+        // The flow graph is left untouched
+        assert_eq!(topology, flow.topology());
+        assert!(flow.node(c).events.is_empty());
+        assert_eq!(vec![("output_connected", 0)], flow.node(a).events);
+        assert_eq!(vec![("input_connected", 0)], flow.node(b).events);
+    }
+
+    #[test]
+    fn notify_nodes_about_connections() {
+        let mut flow: Flow<HookRecorder, (), i32> = Flow::new();
+        let a = flow.add_node(HookRecorder::default());
+        let b = flow.add_node(HookRecorder::default());
+        let c = flow.add_node(HookRecorder::default());
+        flow.connect(socket(a, 0), socket(b, 0));
+        assert_eq!(vec![("output_connected", 0)], flow.node(a).events);
+        assert_eq!(vec![("input_connected", 0)], flow.node(b).events);
+        flow.node_mut(a).output.incoming = Some(());
+        assert_eq!(Some(socket(b, 0)), flow.disconnect_output(socket(a, 0)));
+        assert_eq!(
+            vec![("output_connected", 0), ("output_disconnected", 0)],
+            flow.node(a).events
+        );
+        assert_eq!(
+            vec![("input_connected", 0), ("input_disconnected", 0)],
+            flow.node(b).events
+        );
+        // The control of the disconnected output is discarded
+        assert_eq!(None, flow.node(a).output.incoming);
+        // Nothing to disconnect
+        assert_eq!(None, flow.disconnect_input(socket(b, 0)));
+        assert_eq!(2, flow.node(b).events.len());
+        flow.connect(socket(b, 0), socket(c, 0));
+        flow.remove_node(c);
+        assert_eq!(
+            vec![
+                ("input_connected", 0),
+                ("input_disconnected", 0),
+                ("output_connected", 0),
+                ("output_disconnected", 0),
+            ],
+            flow.node(b).events
+        );
+    }
+
+    #[test]
+    fn swap_nodes_keeps_connections_and_notifies_nodes() {
+        let mut flow: Flow<HookRecorder, (), i32> = Flow::new();
+        let a = flow.add_node(HookRecorder::default());
+        let b = flow.add_node(HookRecorder::default());
+        let c = flow.add_node(HookRecorder::default());
+        flow.connect(socket(a, 0), socket(b, 0));
+        // Only c has an active output
+        flow.node_mut(c).output.incoming = Some(());
+        flow.node_mut(c).input.incoming = Some(7);
+        for node_id in &[a, b, c] {
+            flow.node_mut(*node_id).events.clear();
+        }
+        flow.swap_nodes(a, c);
+        // The connections stay in place
+        assert_eq!(
+            vec![(socket(a, 0), socket(b, 0))],
+            flow.connections().collect::<Vec<_>>()
+        );
+        // The node from c has moved into the connected slot of a
+        assert_eq!(Some(7), flow.node(a).input.incoming);
+        assert_eq!(vec![("output_connected", 0)], flow.node(a).events);
+        assert_eq!(None, flow.node(c).input.incoming);
+        assert_eq!(vec![("output_disconnected", 0)], flow.node(c).events);
+        // Stale control of the previously connected output is discarded
+        assert_eq!(None, flow.node(c).output.incoming);
+        // Control of the unconnected output remains
+        assert_eq!(Some(()), flow.node(a).output.incoming);
+        assert!(flow.node(b).events.is_empty());
+    }
+
+    #[test]
+    fn swap_nodes_with_itself() {
+        let mut flow: Flow<HookRecorder, (), i32> = Flow::new();
+        let a = flow.add_node(HookRecorder::default());
+        let b = flow.add_node(HookRecorder::default());
+        flow.connect(socket(a, 0), socket(b, 0));
+        flow.node_mut(a).events.clear();
+        flow.swap_nodes(a, a);
+        assert!(flow.node(a).events.is_empty());
+    }
 }
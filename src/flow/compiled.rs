@@ -0,0 +1,273 @@
+use super::{disjoint_pair_mut, AccessToken, Cycle, Flow, NodeId};
+
+use crate::{node::Node, port::PortIndex};
+
+use std::{marker::PhantomData, ops::Range};
+
+/// A connection between two ports, seen from one of the nodes
+#[derive(Debug, Clone, Copy)]
+struct Connection {
+    /// The port of the node
+    port_index: PortIndex,
+
+    /// The index of the connected node
+    peer_index: usize,
+
+    /// The port of the connected node
+    peer_port_index: PortIndex,
+}
+
+/// The connections of all nodes in a single, flat array
+#[derive(Debug, Clone, Default)]
+struct Connections {
+    connections: Vec<Connection>,
+    offsets: Vec<usize>,
+}
+
+impl Connections {
+    fn of(&self, index: usize) -> Range<usize> {
+        self.offsets[index]..self.offsets[index + 1]
+    }
+}
+
+/// An immutable flow graph that is optimized for execution
+///
+/// The topological ordering is computed only once when compiling
+/// the flow graph and all connections are stored in flat arrays.
+/// Nodes could still be accessed and modified, but neither added
+/// nor removed nor connected.
+///
+/// Edge probes of the original flow graph are discarded.
+#[derive(Debug)]
+pub struct CompiledFlow<N, S, P> {
+    // Gaps of removed nodes are retained to keep all ids valid
+    nodes: Vec<Option<N>>,
+    topological_order: Vec<NodeId>,
+    level_offsets: Vec<usize>,
+    inputs: Connections,
+    outputs: Connections,
+    phantom1: PhantomData<S>,
+    phantom2: PhantomData<P>,
+}
+
+impl<N, S, P> Flow<N, S, P>
+where
+    N: Node<S, P>,
+{
+    /// Freeze the flow graph for efficient execution
+    ///
+    /// Fails if the flow graph contains a cycle. The flow graph
+    /// is lost in this case.
+    pub fn compile(self) -> Result<CompiledFlow<N, S, P>, Cycle> {
+        let layers = self.topological_layers()?;
+        let mut topological_order = Vec::with_capacity(self.nodes.len());
+        let mut level_offsets = Vec::with_capacity(layers.len() + 1);
+        for layer in layers {
+            level_offsets.push(topological_order.len());
+            topological_order.extend(layer);
+        }
+        level_offsets.push(topological_order.len());
+        let mut inputs = Connections::default();
+        let mut outputs = Connections::default();
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        for flow_node in self.nodes {
+            inputs.offsets.push(inputs.connections.len());
+            outputs.offsets.push(outputs.connections.len());
+            let flow_node = if let Some(flow_node) = flow_node {
+                flow_node
+            } else {
+                nodes.push(None);
+                continue;
+            };
+            inputs
+                .connections
+                .extend(
+                    flow_node
+                        .connected_inputs
+                        .iter()
+                        .map(|(input_index, output)| Connection {
+                            port_index: *input_index,
+                            peer_index: usize::from(output.node_id),
+                            peer_port_index: output.port_index,
+                        }),
+                );
+            outputs
+                .connections
+                .extend(
+                    flow_node
+                        .connected_outputs
+                        .iter()
+                        .map(|(output_index, input)| Connection {
+                            port_index: *output_index,
+                            peer_index: usize::from(input.node_id),
+                            peer_port_index: input.port_index,
+                        }),
+                );
+            nodes.push(Some(flow_node.node));
+        }
+        inputs.offsets.push(inputs.connections.len());
+        outputs.offsets.push(outputs.connections.len());
+        Ok(CompiledFlow {
+            nodes,
+            topological_order,
+            level_offsets,
+            inputs,
+            outputs,
+            phantom1: PhantomData,
+            phantom2: PhantomData,
+        })
+    }
+}
+
+impl<N, S, P> CompiledFlow<N, S, P>
+where
+    N: Node<S, P>,
+{
+    pub fn num_nodes(&self) -> usize {
+        self.topological_order.len()
+    }
+
+    pub fn node(&self, node_id: NodeId) -> &N {
+        self.nodes[usize::from(node_id)]
+            .as_ref()
+            .expect("node has not been removed")
+    }
+
+    pub fn node_mut(&mut self, node_id: NodeId) -> &mut N {
+        self.nodes[usize::from(node_id)]
+            .as_mut()
+            .expect("node has not been removed")
+    }
+
+    /// The topological ordering of all nodes, grouped by level
+    pub fn topological_order(&self) -> &[NodeId] {
+        &self.topological_order
+    }
+
+    /// The nodes on each level of the flow graph
+    ///
+    /// Nodes on the first level have no predecessors. All
+    /// predecessors of a node are located on preceding levels.
+    pub fn levels(&self) -> impl Iterator<Item = &[NodeId]> + '_ {
+        self.level_offsets
+            .windows(2)
+            .map(move |offsets| &self.topological_order[offsets[0]..offsets[1]])
+    }
+
+    /// Execute both passes for all nodes
+    ///
+    /// Returns the nodes that passed on values to subsequent
+    /// nodes during the forward pass in topological order.
+    ///
+    /// See also: `Flow::process_all()`
+    pub fn process_all(&mut self) -> Vec<NodeId> {
+        self.process_backward_all();
+        self.process_forward_all()
+    }
+
+    /// Execute only the backward pass for all nodes
+    ///
+    /// See also: `Flow::process_backward_all()`
+    pub fn process_backward_all(&mut self) {
+        for node_id in self.topological_order.iter().rev() {
+            let index = usize::from(*node_id);
+            self.nodes[index]
+                .as_mut()
+                .expect("node has not been removed")
+                .process_outputs(AccessToken::new());
+            for connection in &self.inputs.connections[self.inputs.of(index)] {
+                let (node, predecessor_node) =
+                    disjoint_pair_mut(&mut self.nodes, index, connection.peer_index);
+                if let Some(packet) =
+                    node.try_dispatch_input_packet(AccessToken::new(), connection.port_index)
+                {
+                    predecessor_node.accept_output_packet(
+                        AccessToken::new(),
+                        connection.peer_port_index,
+                        packet,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Execute only the forward pass for all nodes
+    ///
+    /// Returns the nodes that passed on values to subsequent
+    /// nodes in topological order.
+    ///
+    /// See also: `Flow::process_forward_all()`
+    pub fn process_forward_all(&mut self) -> Vec<NodeId> {
+        let mut dispatched_nodes = Vec::new();
+        for node_id in &self.topological_order {
+            let index = usize::from(*node_id);
+            self.nodes[index]
+                .as_mut()
+                .expect("node has not been removed")
+                .process_inputs(AccessToken::new());
+            let mut dispatched = false;
+            for connection in &self.outputs.connections[self.outputs.of(index)] {
+                let (node, successor_node) =
+                    disjoint_pair_mut(&mut self.nodes, index, connection.peer_index);
+                if let Some(packet) =
+                    node.try_dispatch_output_packet(AccessToken::new(), connection.port_index)
+                {
+                    successor_node.accept_input_packet(
+                        AccessToken::new(),
+                        connection.peer_port_index,
+                        packet,
+                    );
+                    dispatched = true;
+                }
+            }
+            if dispatched {
+                dispatched_nodes.push(*node_id);
+            }
+        }
+        dispatched_nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::fixtures::*;
+
+    /// Process two identical flow graphs, one of them compiled,
+    /// side by side and compare the results of all passes
+    fn assert_same_results(build_flow: impl Fn() -> (AdderFlow, NodeId)) {
+        let (mut flow, sink_id) = build_flow();
+        let (compiled_flow, _) = build_flow();
+        let mut compiled_flow = compiled_flow.compile().unwrap();
+        for _ in 0..3 {
+            unwrap_node!(flow.node_mut(sink_id), AdderTestNode::Sink).request_all_inputs(());
+            unwrap_node!(compiled_flow.node_mut(sink_id), AdderTestNode::Sink)
+                .request_all_inputs(());
+            assert_eq!(flow.process_all().unwrap(), compiled_flow.process_all());
+        }
+        let sink = unwrap_node!(flow.node(sink_id), AdderTestNode::Sink);
+        let compiled_sink = unwrap_node!(compiled_flow.node(sink_id), AdderTestNode::Sink);
+        assert_eq!(3, recent_values(compiled_sink, 0).len());
+        assert_eq!(recent_values(sink, 0), recent_values(compiled_sink, 0));
+    }
+
+    #[test]
+    fn process_all_like_flow() {
+        assert_same_results(|| {
+            let (flow, [.., sink_id], _) = adder_flow(counter_source(1, 1), counter_source(10, 10));
+            (flow, sink_id)
+        });
+    }
+
+    #[test]
+    fn process_all_like_flow_with_removed_nodes() {
+        assert_same_results(|| {
+            let (mut flow, [_, rhs_id, _, sink_id], _) =
+                adder_flow(counter_source(1, 1), counter_source(10, 10));
+            // Leave a gap between the remaining nodes
+            flow.remove_node(rhs_id).unwrap();
+            (flow, sink_id)
+        });
+    }
+}
@@ -0,0 +1,339 @@
+use super::{AccessToken, Flow, Socket};
+
+use crate::{
+    node::*,
+    port::{Packet, Port, PortBay, PortIndex, VecPortBay},
+};
+
+use std::cell::Cell;
+
+thread_local! {
+    static NESTING_DEPTH: Cell<usize> = const { Cell::new(0) };
+
+    static MAX_NESTING_DEPTH: Cell<usize> = const { Cell::new(usize::MAX) };
+}
+
+/// Tracks the nesting depth of composite nodes that are
+/// currently processed on this thread
+///
+/// The limits of all enclosing composite nodes apply to
+/// the nested composite nodes.
+struct NestingGuard {
+    outer_max_depth: usize,
+}
+
+impl NestingGuard {
+    fn enter(max_depth: usize) -> Self {
+        let depth = NESTING_DEPTH.with(|depth| {
+            let next_depth = depth.get() + 1;
+            depth.set(next_depth);
+            next_depth
+        });
+        let outer_max_depth = MAX_NESTING_DEPTH
+            .with(|outer_max_depth| outer_max_depth.replace(outer_max_depth.get().min(max_depth)));
+        // Create the guard before panicking to restore the
+        // depth while unwinding
+        let guard = Self { outer_max_depth };
+        let max_depth = outer_max_depth.min(max_depth);
+        if depth > max_depth {
+            panic!(
+                "Maximum nesting depth of composite nodes exceeded: {} > {}",
+                depth, max_depth
+            );
+        }
+        guard
+    }
+}
+
+impl Drop for NestingGuard {
+    fn drop(&mut self) {
+        NESTING_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        MAX_NESTING_DEPTH.with(|max_depth| max_depth.set(self.outer_max_depth));
+    }
+}
+
+/// A flow graph that is wrapped into a single node
+///
+/// The inputs and outputs of the composite node are mapped
+/// onto unconnected input and output sockets of the inner
+/// flow graph. Both passes are executed for all inner nodes
+/// when processing the composite node.
+///
+/// Composite nodes could be nested. The nesting depth is limited
+/// to prevent stack overflows when processing deeply nested
+/// composite nodes. Exceeding the maximum depth results in a panic.
+#[derive(Debug)]
+pub struct CompositeNode<N, C, D> {
+    flow: Flow<N, C, D>,
+    input_sockets: Vec<Socket>,
+    output_sockets: Vec<Socket>,
+    inputs: VecPortBay<D, C>,
+    outputs: VecPortBay<C, D>,
+    max_depth: usize,
+}
+
+impl<N, C, D> CompositeNode<N, C, D>
+where
+    N: Node<C, D>,
+{
+    /// The default limit for the nesting depth
+    pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+    /// Wrap a flow graph
+    ///
+    /// The input and output sockets of the inner flow graph
+    /// that are exposed as inputs and outputs of the composite
+    /// node must not be connected.
+    pub fn new(
+        flow: Flow<N, C, D>,
+        input_sockets: Vec<Socket>,
+        output_sockets: Vec<Socket>,
+    ) -> Self {
+        debug_assert!(flow.topological_nodes().is_ok());
+        let inputs = VecPortBay::new(input_sockets.len());
+        let outputs = VecPortBay::new(output_sockets.len());
+        Self {
+            flow,
+            input_sockets,
+            output_sockets,
+            inputs,
+            outputs,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    pub fn flow(&self) -> &Flow<N, C, D> {
+        &self.flow
+    }
+
+    pub fn flow_mut(&mut self) -> &mut Flow<N, C, D> {
+        &mut self.flow
+    }
+
+    pub fn into_inner(self) -> Flow<N, C, D> {
+        self.flow
+    }
+
+    /// The maximum nesting depth, including this node
+    ///
+    /// Also limits the depth of all nested composite nodes.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+}
+
+impl<N, C, D> Node<C, D> for CompositeNode<N, C, D>
+where
+    N: Node<C, D> + 'static,
+    C: Clone + 'static,
+    D: 'static,
+{
+}
+
+impl<N, C, D> NodeInputs<C, D> for CompositeNode<N, C, D>
+where
+    N: Node<C, D>,
+{
+    fn num_inputs(&self) -> usize {
+        self.inputs.num_ports()
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        self.inputs.accept_packet(input_index, packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        self.inputs.try_dispatch_packet(input_index)
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.inputs.get_port_mut(input_index).map(f).is_some()
+    }
+}
+
+impl<N, C, D> NodeOutputs<C, D> for CompositeNode<N, C, D>
+where
+    N: Node<C, D>,
+{
+    fn num_outputs(&self) -> usize {
+        self.outputs.num_ports()
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        self.outputs.accept_packet(output_index, packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        self.outputs.try_dispatch_packet(output_index)
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        self.outputs.get_port_mut(output_index).map(f).is_some()
+    }
+}
+
+impl<N, C, D> NodeProcessor for CompositeNode<N, C, D>
+where
+    N: Node<C, D>,
+    C: Clone,
+{
+    fn process_outputs(&mut self, _: AccessToken) {
+        let _guard = NestingGuard::enter(self.max_depth);
+        for (output_port, socket) in self.outputs.ports_mut().zip(self.output_sockets.iter()) {
+            if let Some(ctrl) = &output_port.incoming {
+                let packet = Packet {
+                    payload: ctrl.clone(),
+                    piggyback: output_port.outgoing.take(),
+                };
+                self.flow.node_mut(socket.node_id).accept_output_packet(
+                    AccessToken::new(),
+                    socket.port_index,
+                    packet,
+                );
+            }
+        }
+        self.flow
+            .process_backward_all()
+            .expect("acyclic flow graph");
+        for (input_port, socket) in self.inputs.ports_mut().zip(self.input_sockets.iter()) {
+            let packet = self
+                .flow
+                .node_mut(socket.node_id)
+                .try_dispatch_input_packet(AccessToken::new(), socket.port_index);
+            if let Some(Packet { payload, piggyback }) = packet {
+                input_port.outgoing = Some(payload);
+                input_port.incoming = piggyback;
+            } else {
+                input_port.outgoing = None;
+            }
+        }
+    }
+
+    fn process_inputs(&mut self, _: AccessToken) {
+        let _guard = NestingGuard::enter(self.max_depth);
+        for (input_port, socket) in self.inputs.ports_mut().zip(self.input_sockets.iter()) {
+            if let Some(value) = input_port.incoming.take() {
+                let packet = Packet {
+                    payload: value,
+                    piggyback: input_port.outgoing.take(),
+                };
+                self.flow.node_mut(socket.node_id).accept_input_packet(
+                    AccessToken::new(),
+                    socket.port_index,
+                    packet,
+                );
+            }
+        }
+        self.flow.process_forward_all().expect("acyclic flow graph");
+        for (output_port, socket) in self.outputs.ports_mut().zip(self.output_sockets.iter()) {
+            let packet = self
+                .flow
+                .node_mut(socket.node_id)
+                .try_dispatch_output_packet(AccessToken::new(), socket.port_index);
+            if let Some(Packet { payload, piggyback }) = packet {
+                output_port.outgoing = Some(payload);
+                output_port.incoming = piggyback;
+            } else {
+                output_port.outgoing = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::flow::NodeId;
+
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type BoxedNode = Box<dyn Node<(), i32>>;
+
+    fn socket(node_id: NodeId) -> Socket {
+        Socket {
+            node_id,
+            port_index: PortIndex::new(0),
+        }
+    }
+
+    /// Nest a splitter into the given number of composite nodes
+    fn nested_composite(depth: usize) -> CompositeNode<BoxedNode, (), i32> {
+        let mut node: BoxedNode = Box::new(OneToManySplitter::<(), i32>::new(1));
+        for _ in 1..depth {
+            node = Box::new(wrap(node));
+        }
+        wrap(node)
+    }
+
+    fn wrap(node: BoxedNode) -> CompositeNode<BoxedNode, (), i32> {
+        let mut flow = Flow::new();
+        let node_id = flow.add_node(node);
+        CompositeNode::new(flow, vec![socket(node_id)], vec![socket(node_id)])
+    }
+
+    /// Execute both passes and return the output value
+    fn process(node: &mut CompositeNode<BoxedNode, (), i32>, value: i32) -> Option<i32> {
+        node.outputs.port_mut(PortIndex::new(0)).incoming = Some(());
+        node.process_outputs(AccessToken::new());
+        node.inputs.port_mut(PortIndex::new(0)).incoming = Some(value);
+        node.process_inputs(AccessToken::new());
+        node.outputs.port_mut(PortIndex::new(0)).outgoing.take()
+    }
+
+    #[test]
+    fn process_nested_composite_nodes() {
+        let mut node = nested_composite(3);
+        node.set_max_depth(3);
+        assert_eq!(Some(1), process(&mut node, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Maximum nesting depth of composite nodes exceeded: 4 > 3")]
+    fn panic_when_exceeding_max_depth() {
+        let mut node = nested_composite(4);
+        node.set_max_depth(3);
+        process(&mut node, 1);
+    }
+
+    #[test]
+    fn restore_depth_after_panic() {
+        let mut node = nested_composite(3);
+        node.set_max_depth(2);
+        assert!(catch_unwind(AssertUnwindSafe(|| process(&mut node, 1))).is_err());
+        assert_eq!(0, NESTING_DEPTH.with(Cell::get));
+        assert_eq!(usize::MAX, MAX_NESTING_DEPTH.with(Cell::get));
+        node.set_max_depth(3);
+        assert_eq!(Some(2), process(&mut node, 2));
+    }
+}
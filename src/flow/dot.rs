@@ -0,0 +1,158 @@
+use super::{Flow, NodeId};
+
+use crate::node::Node;
+
+use std::fmt::Write as _;
+
+/// Escape a label for a quoted string in the DOT language
+fn escape_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Visualization with Graphviz
+///
+/// Nodes are named `n<id>` and emitted in ascending order of their
+/// ids. Edges are labeled with the indexes of the output and input
+/// ports and ordered by output. The output is deterministic and could
+/// be rendered with `dot`, e.g. `dot -Tpng`.
+impl<N, S, P> Flow<N, S, P>
+where
+    N: Node<S, P>,
+{
+    /// Render the flow graph in the DOT language, labeling
+    /// all nodes with their ids
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(|node_id, _| usize::from(node_id).to_string())
+    }
+
+    /// Render the flow graph in the DOT language with custom
+    /// node labels
+    pub fn to_dot_with<F>(&self, label: F) -> String
+    where
+        F: Fn(NodeId, &N) -> String,
+    {
+        let mut dot = String::from("digraph flow {\n");
+        for (node_id, flow_node) in self.flow_nodes() {
+            // Writing into a String never fails
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\"];",
+                usize::from(node_id),
+                escape_label(&label(node_id, &flow_node.node))
+            );
+        }
+        let mut connections: Vec<_> = self.connections().collect();
+        connections.sort_unstable_by_key(|(output, _)| (output.node_id, output.port_index));
+        for (output, input) in connections {
+            let _ = writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}->{}\"];",
+                usize::from(output.node_id),
+                usize::from(input.node_id),
+                usize::from(output.port_index),
+                usize::from(input.port_index)
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        node::{CalcNode, DebugPrinterSink, NodeInputs, NodeOutputs, OneToManySplitter},
+        testing::fixtures::socket,
+    };
+
+    #[test]
+    fn escape_labels() {
+        assert_eq!("plain", escape_label("plain"));
+        assert_eq!(r#"say \"hi\""#, escape_label(r#"say "hi""#));
+        assert_eq!(r"C:\\flow", escape_label(r"C:\flow"));
+        assert_eq!(r"line\nbreak", escape_label("line\nbreak"));
+    }
+
+    #[test]
+    fn render_nodes_and_edges_in_order() {
+        let mut flow: Flow<OneToManySplitter<(), i32>, (), i32> = Flow::new();
+        let a = flow.add_node(OneToManySplitter::new(2));
+        let b = flow.add_node(OneToManySplitter::new(1));
+        let c = flow.add_node(OneToManySplitter::new(1));
+        let d = flow.add_node(OneToManySplitter::new(1));
+        let removed = flow.add_node(OneToManySplitter::new(1));
+        flow.connect(socket(d, 0), socket(removed, 0));
+        flow.connect(socket(b, 0), socket(d, 0));
+        flow.connect(socket(a, 1), socket(c, 0));
+        flow.connect(socket(a, 0), socket(b, 0));
+        flow.remove_node(removed);
+        assert_eq!(
+            concat!(
+                "digraph flow {\n",
+                "    n0 [label=\"0\"];\n",
+                "    n1 [label=\"1\"];\n",
+                "    n2 [label=\"2\"];\n",
+                "    n3 [label=\"3\"];\n",
+                "    n0 -> n1 [label=\"0->0\"];\n",
+                "    n0 -> n2 [label=\"1->0\"];\n",
+                "    n1 -> n3 [label=\"0->0\"];\n",
+                "}\n",
+            ),
+            flow.to_dot()
+        );
+        let dot = flow.to_dot_with(|node_id, _| format!("\"{}\"", usize::from(node_id)));
+        assert!(dot.contains(r#"n3 [label="\"3\""];"#));
+    }
+
+    crate::node_enum! {
+        enum CalcFlowNode: Node<(), f64> {
+            Splitter(OneToManySplitter<(), f64>),
+            Calc(CalcNode<()>),
+            Printer(DebugPrinterSink<(), f64>),
+        }
+    }
+
+    #[test]
+    fn render_edges_of_calculator_flow() {
+        let calculator = CalcNode::<()>::with_default_operations();
+        let splitter = OneToManySplitter::<(), f64>::new(calculator.num_inputs());
+        let printer = DebugPrinterSink::<(), f64>::new(calculator.num_outputs());
+        let mut flow: Flow<CalcFlowNode, (), f64> = Flow::new();
+        let printer_id = flow.add_node(printer.into());
+        let splitter_id = flow.add_node(splitter.into());
+        let calculator_id = flow.add_node(calculator.into());
+        for port_index in 0..flow.node(splitter_id).num_outputs() {
+            flow.connect(
+                socket(splitter_id, port_index),
+                socket(calculator_id, port_index),
+            );
+        }
+        for port_index in 0..flow.node(calculator_id).num_outputs() {
+            flow.connect(
+                socket(calculator_id, port_index),
+                socket(printer_id, port_index),
+            );
+        }
+        let dot = flow.to_dot();
+        // 2 edges from the splitter and 5 edges from the calculator
+        assert_eq!(7, dot.lines().filter(|line| line.contains("->")).count());
+        assert!(dot.contains(r#"n1 -> n2 [label="0->0"];"#));
+        assert!(dot.contains(r#"n1 -> n2 [label="1->1"];"#));
+        assert!(dot.contains(r#"n2 -> n0 [label="2->2"];"#));
+        assert!(dot.contains(r#"n2 -> n0 [label="4->4"];"#));
+        assert!(!dot.contains("n0 ->"));
+    }
+}
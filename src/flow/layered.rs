@@ -0,0 +1,271 @@
+use super::{AccessToken, Cycle, EdgeProbe, Flow, FlowNode, NodeId, Phase, Socket};
+
+use crate::node::Node;
+
+use rayon::prelude::*;
+
+/// Borrow all nodes of a topological layer mutably at the same time
+///
+/// Returns the nodes in order of their ids.
+fn layer_nodes_mut<'a, N>(
+    nodes: &'a mut [Option<FlowNode<N>>],
+    layer: &[NodeId],
+) -> Vec<(NodeId, &'a mut FlowNode<N>)> {
+    let mut node_ids = layer.to_vec();
+    node_ids.sort_unstable();
+    let mut layer_nodes = Vec::with_capacity(node_ids.len());
+    let mut remaining = nodes;
+    let mut offset = 0;
+    for node_id in node_ids {
+        let index = usize::from(node_id);
+        let (_, tail) = std::mem::take(&mut remaining).split_at_mut(index - offset);
+        let (flow_node, tail) = tail.split_first_mut().expect("existing node");
+        layer_nodes.push((
+            node_id,
+            flow_node.as_mut().expect("node has not been removed"),
+        ));
+        remaining = tail;
+        offset = index + 1;
+    }
+    layer_nodes
+}
+
+/// Parallel execution of topological layers with rayon
+///
+/// Nodes within a topological layer are never connected with each
+/// other and are processed in parallel. Their packets are collected
+/// and passed on to the nodes in other layers afterwards. The results
+/// are therefore the same as for sequential execution, as long as
+/// nodes don't share any state.
+///
+/// Edge probes are invoked and process events are recorded while
+/// passing on the packets.
+impl<N, S, P> Flow<N, S, P>
+where
+    N: Node<S, P> + Send,
+    S: Send,
+    P: Send,
+{
+    /// Execute both passes for all nodes, processing the nodes
+    /// of each topological layer in parallel
+    ///
+    /// The layers are recomputed for each invocation.
+    ///
+    /// See also: `execute()`, `topological_layers()`
+    pub fn execute_parallel(&mut self) -> Result<(), Cycle> {
+        let layers = self.topological_layers()?;
+        for layer in layers.iter().rev() {
+            self.process_layer_outputs(layer);
+        }
+        for layer in &layers {
+            self.process_layer_inputs(layer);
+        }
+        Ok(())
+    }
+
+    /// Execute the backward pass for all nodes of a single layer
+    fn process_layer_outputs(&mut self, layer: &[NodeId]) {
+        let mut layer_nodes = layer_nodes_mut(&mut self.nodes, layer);
+        let packets: Vec<Vec<_>> = layer_nodes
+            .par_iter_mut()
+            .map(|(_, flow_node)| {
+                let FlowNode {
+                    node,
+                    connected_inputs,
+                    ..
+                } = &mut **flow_node;
+                node.process_outputs(AccessToken::new());
+                connected_inputs
+                    .iter()
+                    .filter_map(|(input_index, output)| {
+                        node.try_dispatch_input_packet(AccessToken::new(), *input_index)
+                            .map(|packet| (*output, packet))
+                    })
+                    .collect()
+            })
+            .collect();
+        let node_ids: Vec<_> = layer_nodes
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .collect();
+        // All predecessors are located in preceding layers
+        for (node_id, packets) in node_ids.into_iter().zip(packets) {
+            self.record_process_event(node_id, Phase::Backward);
+            for (output, packet) in packets {
+                let Socket {
+                    node_id: predecessor_node_id,
+                    port_index: predecessor_port_index,
+                } = output;
+                self.flow_node_mut(predecessor_node_id)
+                    .node
+                    .accept_output_packet(AccessToken::new(), predecessor_port_index, packet);
+            }
+        }
+    }
+
+    /// Execute the forward pass for all nodes of a single layer
+    fn process_layer_inputs(&mut self, layer: &[NodeId]) {
+        let mut layer_nodes = layer_nodes_mut(&mut self.nodes, layer);
+        let packets: Vec<Vec<_>> = layer_nodes
+            .par_iter_mut()
+            .map(|(_, flow_node)| {
+                let FlowNode {
+                    node,
+                    connected_outputs,
+                    ..
+                } = &mut **flow_node;
+                node.process_inputs(AccessToken::new());
+                connected_outputs
+                    .iter()
+                    .filter_map(|(output_index, input)| {
+                        node.try_dispatch_output_packet(AccessToken::new(), *output_index)
+                            .map(|packet| (*output_index, *input, packet))
+                    })
+                    .collect()
+            })
+            .collect();
+        let node_ids: Vec<_> = layer_nodes
+            .into_iter()
+            .map(|(node_id, _)| node_id)
+            .collect();
+        // All successors are located in subsequent layers
+        for (node_id, packets) in node_ids.into_iter().zip(packets) {
+            self.record_process_event(node_id, Phase::Forward);
+            for (output_index, input, packet) in packets {
+                if !self.edge_probes.is_empty() {
+                    let output = Socket {
+                        node_id,
+                        port_index: output_index,
+                    };
+                    if let Some(EdgeProbe(probe)) = self.edge_probes.get_mut(&output) {
+                        probe(&packet.payload);
+                    }
+                }
+                let Socket {
+                    node_id: successor_node_id,
+                    port_index: successor_port_index,
+                } = input;
+                self.flow_node_mut(successor_node_id)
+                    .node
+                    .accept_input_packet(AccessToken::new(), successor_port_index, packet);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        node::{FnNode, OneToManySplitter, RingBufferSink},
+        port::PortIndex,
+        testing::fixtures::*,
+    };
+
+    type CalcFn = fn(&[Option<f64>], &mut [Option<f64>]);
+
+    fn calculate(inputs: &[Option<f64>], outputs: &mut [Option<f64>]) {
+        let (lhs, rhs) = (inputs[0], inputs[1]);
+        outputs[0] = lhs.map(|lhs| -lhs);
+        outputs[1] = rhs.map(|rhs| -rhs);
+        if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+            outputs[2] = Some(lhs + rhs);
+            outputs[3] = Some(lhs - rhs);
+            outputs[4] = Some(lhs * rhs);
+        }
+    }
+
+    const NUM_OUTPUTS: usize = 5;
+
+    crate::node_enum! {
+        enum CalcTestNode: Node<(), f64> {
+            Splitter(OneToManySplitter<(), f64>),
+            Calc(FnNode<(), f64, CalcFn>),
+            Sink(RingBufferSink<(), f64>),
+        }
+    }
+
+    /// Two calculators with a shared splitter for their inputs
+    ///
+    /// Returns the flow graph, the splitter, and the sink.
+    fn calculator_flow() -> (Flow<CalcTestNode, (), f64>, NodeId, NodeId) {
+        let mut flow = Flow::new();
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(2 * NUM_OUTPUTS, 8).into());
+        let splitter_id = flow.add_node(OneToManySplitter::new(4).into());
+        for calc_index in 0..2 {
+            let calc_id = flow.add_node(FnNode::new(2, NUM_OUTPUTS, calculate as CalcFn).into());
+            for input_index in 0..2 {
+                flow.connect(
+                    socket(splitter_id, 2 * calc_index + input_index),
+                    socket(calc_id, input_index),
+                );
+            }
+            for output_index in 0..NUM_OUTPUTS {
+                flow.connect(
+                    socket(calc_id, output_index),
+                    socket(sink_id, calc_index * NUM_OUTPUTS + output_index),
+                );
+            }
+        }
+        (flow, splitter_id, sink_id)
+    }
+
+    fn execute_passes(parallel: bool) -> Vec<Vec<f64>> {
+        let (mut flow, splitter_id, sink_id) = calculator_flow();
+        for value in 0..5 {
+            let sink = unwrap_node!(flow.node_mut(sink_id), CalcTestNode::Sink);
+            // All outputs of the first calculator and only
+            // the sum of the second calculator
+            for input_index in 0..NUM_OUTPUTS {
+                sink.request_input(PortIndex::new(input_index), ());
+            }
+            sink.request_input(PortIndex::new(NUM_OUTPUTS + 2), ());
+            unwrap_node!(flow.node_mut(splitter_id), CalcTestNode::Splitter)
+                .input_mut()
+                .incoming = Some(f64::from(value));
+            if parallel {
+                flow.execute_parallel().unwrap();
+            } else {
+                flow.execute().unwrap();
+            }
+        }
+        let sink = unwrap_node!(flow.node(sink_id), CalcTestNode::Sink);
+        (0..2 * NUM_OUTPUTS)
+            .map(|input_index| recent_values(sink, input_index))
+            .collect()
+    }
+
+    #[test]
+    fn execute_parallel_equals_sequential() {
+        let sequential = execute_passes(false);
+        assert_eq!(vec![0.0, 2.0, 4.0, 6.0, 8.0], sequential[2]);
+        assert_eq!(vec![0.0, 1.0, 4.0, 9.0, 16.0], sequential[4]);
+        assert_eq!(sequential[2], sequential[NUM_OUTPUTS + 2]);
+        assert!(sequential[NUM_OUTPUTS].is_empty());
+        assert_eq!(sequential, execute_passes(true));
+    }
+
+    #[test]
+    fn layer_nodes_in_order_of_ids() {
+        let (mut flow, splitter_id, sink_id) = calculator_flow();
+        let layers = flow.topological_layers().unwrap();
+        assert_eq!(
+            vec![
+                vec![splitter_id],
+                vec![NodeId::new(2), NodeId::new(3)],
+                vec![sink_id]
+            ],
+            layers
+        );
+        let layer = [NodeId::new(3), NodeId::new(2)];
+        let layer_nodes = layer_nodes_mut(&mut flow.nodes, &layer);
+        assert_eq!(
+            vec![NodeId::new(2), NodeId::new(3)],
+            layer_nodes
+                .into_iter()
+                .map(|(node_id, _)| node_id)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use super::{AccessToken, Cycle, Flow, NodeId, Socket};
+
+use crate::node::Node;
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Condvar, Mutex,
+    },
+    thread,
+};
+
+/// Executes nodes on multiple threads as soon as they become ready
+///
+/// Each node has an atomic counter of pending dependencies. A node
+/// becomes ready when the counter drops to zero, i.e. when all its
+/// dependencies have been processed and have passed on their packets.
+/// Ready nodes are pushed onto the work queue of the thread that
+/// released them. Idle threads steal work from the queues of other
+/// threads or wait until new nodes have been released.
+struct Scheduler<'a> {
+    dependents: &'a [Vec<usize>],
+    pending_dependencies: Vec<AtomicUsize>,
+    pending_nodes: AtomicUsize,
+    queues: Vec<Mutex<VecDeque<usize>>>,
+    // Incremented whenever nodes have been released or all
+    // nodes have been processed
+    generation: Mutex<usize>,
+    generation_changed: Condvar,
+}
+
+impl<'a> Scheduler<'a> {
+    fn new(dependents: &'a [Vec<usize>], num_threads: usize) -> Self {
+        let mut num_dependencies = vec![0; dependents.len()];
+        for dependent in dependents.iter().flatten() {
+            num_dependencies[*dependent] += 1;
+        }
+        let mut queues: Vec<_> = (0..num_threads).map(|_| VecDeque::new()).collect();
+        for (thread_index, index) in num_dependencies
+            .iter()
+            .enumerate()
+            .filter(|(_, num_dependencies)| **num_dependencies == 0)
+            .map(|(index, _)| index)
+            .enumerate()
+        {
+            queues[thread_index % num_threads].push_back(index);
+        }
+        Self {
+            dependents,
+            pending_dependencies: num_dependencies.into_iter().map(AtomicUsize::new).collect(),
+            pending_nodes: AtomicUsize::new(dependents.len()),
+            queues: queues.into_iter().map(Mutex::new).collect(),
+            generation: Mutex::new(0),
+            generation_changed: Condvar::new(),
+        }
+    }
+
+    /// Wake up all idle threads
+    fn notify_all(&self) {
+        *self.generation.lock().unwrap() += 1;
+        self.generation_changed.notify_all();
+    }
+
+    /// Wait until the generation has changed
+    fn wait(&self, generation: usize) {
+        let mut current = self.generation.lock().unwrap();
+        while *current == generation && self.pending_nodes.load(Ordering::Acquire) > 0 {
+            current = self.generation_changed.wait(current).unwrap();
+        }
+    }
+
+    fn next_ready(&self, thread_index: usize) -> Option<usize> {
+        // Prefer the most recently released node of this thread
+        if let Some(index) = self.queues[thread_index].lock().unwrap().pop_back() {
+            return Some(index);
+        }
+        let num_threads = self.queues.len();
+        (1..num_threads)
+            .map(|offset| (thread_index + offset) % num_threads)
+            .find_map(|victim| self.queues[victim].lock().unwrap().pop_front())
+    }
+
+    fn run(&self, thread_index: usize, process: &(impl Fn(usize) + Sync)) {
+        while self.pending_nodes.load(Ordering::Acquire) > 0 {
+            // Nodes that are released after reading the generation
+            // are either found or change the generation
+            let generation = *self.generation.lock().unwrap();
+            let index = if let Some(index) = self.next_ready(thread_index) {
+                index
+            } else {
+                self.wait(generation);
+                continue;
+            };
+            process(index);
+            let mut released = false;
+            for dependent in &self.dependents[index] {
+                if self.pending_dependencies[*dependent].fetch_sub(1, Ordering::AcqRel) == 1 {
+                    self.queues[thread_index]
+                        .lock()
+                        .unwrap()
+                        .push_back(*dependent);
+                    released = true;
+                }
+            }
+            // Released dependents are already queued
+            let finished = self.pending_nodes.fetch_sub(1, Ordering::AcqRel) == 1;
+            if released || finished {
+                self.notify_all();
+            }
+        }
+    }
+
+    fn execute(dependents: &[Vec<usize>], num_threads: usize, process: impl Fn(usize) + Sync) {
+        let num_threads = num_threads.max(1).min(dependents.len().max(1));
+        let scheduler = Scheduler::new(dependents, num_threads);
+        let scheduler = &scheduler;
+        let process = &process;
+        thread::scope(|scope| {
+            for thread_index in 1..num_threads {
+                scope.spawn(move || scheduler.run(thread_index, process));
+            }
+            scheduler.run(0, process);
+        });
+    }
+}
+
+/// Parallel execution on multiple threads
+///
+/// Nodes are processed as soon as all of their dependencies have
+/// been processed, without waiting for unrelated nodes. This keeps
+/// all threads busy even if the flow graph is very irregular.
+///
+/// A node is only ever processed by a single thread at a time and
+/// none of its dependents is processed until it has passed on all of
+/// its packets. The results are therefore the same as for sequential
+/// execution, as long as nodes don't share any state.
+///
+/// Edge probes are not invoked and process events are not recorded
+/// during parallel execution.
+impl<N, S, P> Flow<N, S, P>
+where
+    N: Node<S, P> + Send,
+    S: Send,
+    P: Send,
+{
+    /// Execute both passes for all nodes on multiple threads
+    ///
+    /// See also: `process_all()`
+    pub fn process_all_parallel(&mut self, num_threads: usize) -> Result<Vec<NodeId>, Cycle> {
+        self.process_backward_parallel(num_threads)?;
+        self.process_forward_parallel(num_threads)
+    }
+
+    /// Execute only the backward pass for all nodes on multiple
+    /// threads
+    ///
+    /// A node is processed after all of its successors.
+    ///
+    /// See also: `process_backward_all()`
+    pub fn process_backward_parallel(&mut self, num_threads: usize) -> Result<(), Cycle> {
+        self.topological_order()?;
+        let dependents: Vec<Vec<_>> = self
+            .nodes
+            .iter()
+            .map(|flow_node| {
+                flow_node
+                    .iter()
+                    .flat_map(|flow_node| {
+                        flow_node
+                            .connected_inputs
+                            .values()
+                            .map(|output| usize::from(output.node_id))
+                    })
+                    .collect()
+            })
+            .collect();
+        let nodes: Vec<_> = self
+            .nodes
+            .iter_mut()
+            .map(|flow_node| {
+                flow_node
+                    .as_mut()
+                    .map(|flow_node| (Mutex::new(&mut flow_node.node), &flow_node.connected_inputs))
+            })
+            .collect();
+        Scheduler::execute(&dependents, num_threads, |index| {
+            let (node, connections) = if let Some(entry) = &nodes[index] {
+                entry
+            } else {
+                // Removed node
+                return;
+            };
+            let packets: Vec<_> = {
+                let mut node = node.lock().unwrap();
+                node.process_outputs(AccessToken::new());
+                connections
+                    .iter()
+                    .filter_map(|(input_index, output)| {
+                        node.try_dispatch_input_packet(AccessToken::new(), *input_index)
+                            .map(|packet| (*output, packet))
+                    })
+                    .collect()
+            };
+            for (output, packet) in packets {
+                let Socket {
+                    node_id: predecessor_node_id,
+                    port_index: predecessor_port_index,
+                } = output;
+                // The predecessor is not ready before all of its
+                // successors have been processed
+                let (node, _) = nodes[usize::from(predecessor_node_id)]
+                    .as_ref()
+                    .expect("node has not been removed");
+                node.lock().unwrap().accept_output_packet(
+                    AccessToken::new(),
+                    predecessor_port_index,
+                    packet,
+                );
+            }
+        });
+        Ok(())
+    }
+
+    /// Execute only the forward pass for all nodes on multiple
+    /// threads
+    ///
+    /// A node is processed after all of its predecessors.
+    ///
+    /// Returns the nodes that passed on values to subsequent
+    /// nodes in topological order.
+    ///
+    /// See also: `process_forward_all()`
+    pub fn process_forward_parallel(&mut self, num_threads: usize) -> Result<Vec<NodeId>, Cycle> {
+        self.topological_order()?;
+        let dependents: Vec<Vec<_>> = self
+            .nodes
+            .iter()
+            .map(|flow_node| {
+                flow_node
+                    .iter()
+                    .flat_map(|flow_node| {
+                        flow_node
+                            .connected_outputs
+                            .values()
+                            .map(|input| usize::from(input.node_id))
+                    })
+                    .collect()
+            })
+            .collect();
+        let dispatched: Vec<_> = (0..self.nodes.len())
+            .map(|_| AtomicBool::new(false))
+            .collect();
+        let nodes: Vec<_> = self
+            .nodes
+            .iter_mut()
+            .map(|flow_node| {
+                flow_node.as_mut().map(|flow_node| {
+                    (
+                        Mutex::new(&mut flow_node.node),
+                        &flow_node.connected_outputs,
+                    )
+                })
+            })
+            .collect();
+        Scheduler::execute(&dependents, num_threads, |index| {
+            let (node, connections) = if let Some(entry) = &nodes[index] {
+                entry
+            } else {
+                // Removed node
+                return;
+            };
+            let packets: Vec<_> = {
+                let mut node = node.lock().unwrap();
+                node.process_inputs(AccessToken::new());
+                connections
+                    .iter()
+                    .filter_map(|(output_index, input)| {
+                        node.try_dispatch_output_packet(AccessToken::new(), *output_index)
+                            .map(|packet| (*input, packet))
+                    })
+                    .collect()
+            };
+            if !packets.is_empty() {
+                dispatched[index].store(true, Ordering::Relaxed);
+            }
+            for (input, packet) in packets {
+                let Socket {
+                    node_id: successor_node_id,
+                    port_index: successor_port_index,
+                } = input;
+                // The successor is not ready before all of its
+                // predecessors have been processed
+                let (node, _) = nodes[usize::from(successor_node_id)]
+                    .as_ref()
+                    .expect("node has not been removed");
+                node.lock().unwrap().accept_input_packet(
+                    AccessToken::new(),
+                    successor_port_index,
+                    packet,
+                );
+            }
+        });
+        let topo_nodes = self.topological_order()?;
+        Ok(topo_nodes
+            .iter()
+            .copied()
+            .filter(|node_id| dispatched[usize::from(*node_id)].load(Ordering::Relaxed))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{node::OneToManySplitter, port::PortIndex, testing::fixtures::socket};
+
+    const WIDTH: usize = 16;
+
+    type SplitterFlow = Flow<OneToManySplitter<(), i32>, (), i32>;
+
+    /// A single splitter that fans out into parallel chains
+    fn fan_out() -> (SplitterFlow, NodeId, Vec<NodeId>) {
+        let mut flow = Flow::new();
+        let head = flow.add_node(OneToManySplitter::new(WIDTH));
+        let mut tails = Vec::with_capacity(WIDTH);
+        for port_index in 0..WIDTH {
+            let mid = flow.add_node(OneToManySplitter::new(1));
+            let tail = flow.add_node(OneToManySplitter::new(1));
+            flow.connect(socket(head, port_index), socket(mid, 0));
+            flow.connect(socket(mid, 0), socket(tail, 0));
+            // Leave some chains inactive
+            if port_index % 3 != 0 {
+                flow.node_mut(tail).request_output(PortIndex::new(0), ());
+            }
+            tails.push(tail);
+        }
+        (flow, head, tails)
+    }
+
+    fn tail_values(flow: &SplitterFlow, tails: &[NodeId]) -> Vec<Option<i32>> {
+        tails
+            .iter()
+            .map(|tail| flow.node(*tail).output(PortIndex::new(0)).outgoing)
+            .collect()
+    }
+
+    #[test]
+    fn process_all_parallel_equals_sequential() {
+        let (mut sequential, head, tails) = fan_out();
+        sequential.process_backward_all().unwrap();
+        sequential.node_mut(head).input_mut().incoming = Some(1);
+        let sequential_dispatched = sequential.process_forward_all().unwrap();
+        for num_threads in 1..=4 {
+            let (mut parallel, _, _) = fan_out();
+            parallel.process_backward_parallel(num_threads).unwrap();
+            assert!(parallel.node(head).is_input_requested());
+            parallel.node_mut(head).input_mut().incoming = Some(1);
+            let parallel_dispatched = parallel.process_forward_parallel(num_threads).unwrap();
+            assert_eq!(sequential_dispatched, parallel_dispatched);
+            assert_eq!(
+                tail_values(&sequential, &tails),
+                tail_values(&parallel, &tails)
+            );
+        }
+        assert_eq!(
+            Some(1),
+            sequential.node(tails[1]).output(PortIndex::new(0)).outgoing
+        );
+        assert_eq!(
+            None,
+            sequential.node(tails[0]).output(PortIndex::new(0)).outgoing
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use super::{ConnectError, Flow, NodeId, Socket};
+
+use crate::node::{Node, NodeFactory, NodeKind, NodeParams};
+
+/// Rejected topology
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TopologyError {
+    /// The removed node is either out of range or not in
+    /// strictly ascending order
+    InvalidRemovedNode {
+        /// The removed node
+        node_id: NodeId,
+    },
+
+    /// The number of provided nodes doesn't match the topology
+    NodeCountMismatch {
+        /// The number of nodes that have not been removed
+        expected: usize,
+
+        /// The number of provided nodes
+        actual: usize,
+    },
+
+    /// The factory failed to create a node
+    UnsupportedNode {
+        /// The kind of the node
+        kind: String,
+    },
+
+    /// A connection could not be established
+    Connect(ConnectError),
+}
+
+impl From<ConnectError> for TopologyError {
+    fn from(from: ConnectError) -> Self {
+        TopologyError::Connect(from)
+    }
+}
+
+/// The structure of a flow graph without its nodes
+///
+/// Captures the number of nodes and all connections between
+/// them. The nodes themselves are application-specific and
+/// need to be stored separately.
+///
+/// See also: `Flow::topology()`, `Flow::rebuild_from_topology()`,
+/// `Flow::from_topology()`
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlowTopology {
+    /// The number of nodes, including removed nodes
+    pub num_nodes: usize,
+
+    /// The ids of removed nodes in ascending order
+    pub removed_nodes: Vec<NodeId>,
+
+    /// All connections as pairs of output and input sockets,
+    /// ordered by output
+    pub connections: Vec<(Socket, Socket)>,
+
+    /// The kinds and parameters of all nodes that have not
+    /// been removed in order of their ids
+    ///
+    /// Empty if not recorded.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nodes: Vec<NodeDescriptor>,
+}
+
+/// The kind and parameters of a node
+///
+/// See also: `NodeKind`, `NodeFactory`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeDescriptor {
+    pub kind: String,
+
+    pub params: NodeParams,
+}
+
+impl<N, S, P> Flow<N, S, P>
+where
+    N: Node<S, P>,
+{
+    /// Capture the structure of the flow graph
+    ///
+    /// The nodes are not recorded, see `topology_with_nodes()`.
+    pub fn topology(&self) -> FlowTopology {
+        let removed_nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, flow_node)| flow_node.is_none())
+            .map(|(index, _)| NodeId::new(index))
+            .collect();
+        let mut connections: Vec<_> = self.connections().collect();
+        connections.sort_unstable_by_key(|(output, _)| (output.node_id, output.port_index));
+        FlowTopology {
+            num_nodes: self.nodes.len(),
+            removed_nodes,
+            connections,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Capture the structure of the flow graph together with
+    /// the kind and parameters of all nodes
+    ///
+    /// The nodes could be recreated in their initial state
+    /// with a `NodeFactory`, see `from_topology()`.
+    pub fn topology_with_nodes(&self) -> FlowTopology
+    where
+        N: NodeKind,
+    {
+        let nodes = self
+            .flow_nodes()
+            .map(|(_, flow_node)| NodeDescriptor {
+                kind: flow_node.node.kind().to_owned(),
+                params: flow_node.node.params(),
+            })
+            .collect();
+        FlowTopology {
+            nodes,
+            ..self.topology()
+        }
+    }
+
+    /// Recreate a flow graph from its structure, including
+    /// all nodes
+    ///
+    /// The recorded nodes are created with the factory in
+    /// their initial state. See also: `rebuild_from_topology()`
+    pub fn from_topology<F>(topology: &FlowTopology, factory: &F) -> Result<Self, TopologyError>
+    where
+        F: NodeFactory<N>,
+    {
+        let nodes = topology
+            .nodes
+            .iter()
+            .map(|NodeDescriptor { kind, params }| {
+                factory
+                    .create_node(kind, params)
+                    .ok_or_else(|| TopologyError::UnsupportedNode { kind: kind.clone() })
+            })
+            .collect::<Result<_, _>>()?;
+        Self::rebuild_from_topology(nodes, topology)
+    }
+
+    /// Recreate a flow graph from its nodes and structure
+    ///
+    /// The nodes must be provided in order of their ids,
+    /// omitting all removed nodes. All connections are
+    /// validated and established in order.
+    ///
+    /// The topology is rejected if the removed nodes are not
+    /// in strictly ascending order or out of range, if the number
+    /// of nodes doesn't match, or if any of the connections is
+    /// invalid, e.g. refers to a removed or unknown node.
+    pub fn rebuild_from_topology(
+        nodes: Vec<N>,
+        topology: &FlowTopology,
+    ) -> Result<Self, TopologyError> {
+        let mut next_index = 0;
+        for node_id in topology.removed_nodes.iter().copied() {
+            let index = usize::from(node_id);
+            if index < next_index || index >= topology.num_nodes {
+                return Err(TopologyError::InvalidRemovedNode { node_id });
+            }
+            next_index = index + 1;
+        }
+        let expected_num_nodes = topology.num_nodes - topology.removed_nodes.len();
+        if nodes.len() != expected_num_nodes {
+            return Err(TopologyError::NodeCountMismatch {
+                expected: expected_num_nodes,
+                actual: nodes.len(),
+            });
+        }
+        let mut flow = Self::with_capacity(topology.num_nodes);
+        let mut nodes = nodes.into_iter();
+        let mut removed_nodes = topology.removed_nodes.iter().copied().peekable();
+        for index in 0..topology.num_nodes {
+            let node_id = NodeId::new(index);
+            if removed_nodes.next_if_eq(&node_id).is_some() {
+                flow.nodes.push(None);
+            } else {
+                flow.add_node(nodes.next().expect("remaining nodes"));
+            }
+        }
+        debug_assert!(nodes.next().is_none());
+        for (output, input) in &topology.connections {
+            flow.try_connect(*output, *input)?;
+        }
+        Ok(flow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        node::{NodeOutputs, NodeParam, OneToManySplitter},
+        port::PortIndex,
+    };
+
+    type SplitterFlow = Flow<OneToManySplitter<(), i32>, (), i32>;
+
+    fn socket(index: usize, port_index: usize) -> Socket {
+        Socket {
+            node_id: NodeId::new(index),
+            port_index: PortIndex::new(port_index),
+        }
+    }
+
+    fn splitters(num_nodes: usize) -> Vec<OneToManySplitter<(), i32>> {
+        std::iter::repeat_with(|| OneToManySplitter::new(2))
+            .take(num_nodes)
+            .collect()
+    }
+
+    /// a -> b, a -> d with the removed node c
+    fn sample_flow() -> SplitterFlow {
+        let mut flow = Flow::new();
+        for node in splitters(4) {
+            flow.add_node(node);
+        }
+        flow.connect(socket(0, 1), socket(3, 0));
+        flow.connect(socket(0, 0), socket(1, 0));
+        flow.remove_node(NodeId::new(2));
+        flow
+    }
+
+    #[test]
+    fn rebuild_from_topology() {
+        let topology = sample_flow().topology();
+        assert_eq!(4, topology.num_nodes);
+        assert_eq!(vec![NodeId::new(2)], topology.removed_nodes);
+        assert_eq!(
+            vec![(socket(0, 0), socket(1, 0)), (socket(0, 1), socket(3, 0))],
+            topology.connections
+        );
+        let flow = SplitterFlow::rebuild_from_topology(splitters(3), &topology).unwrap();
+        assert!(!flow.contains_node(NodeId::new(2)));
+        assert_eq!(topology, flow.topology());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rebuild_from_json() {
+        let topology = sample_flow().topology();
+        let json = serde_json::to_string(&topology).unwrap();
+        let deserialized: FlowTopology = serde_json::from_str(&json).unwrap();
+        assert_eq!(topology, deserialized);
+        let flow = SplitterFlow::rebuild_from_topology(splitters(3), &deserialized).unwrap();
+        assert_eq!(topology, flow.topology());
+    }
+
+    #[test]
+    fn reject_invalid_removed_nodes() {
+        for removed_nodes in &[vec![2, 1], vec![1, 1], vec![4]] {
+            let topology = FlowTopology {
+                num_nodes: 4,
+                removed_nodes: removed_nodes.iter().copied().map(NodeId::new).collect(),
+                connections: Vec::new(),
+                nodes: Vec::new(),
+            };
+            assert_eq!(
+                Err(TopologyError::InvalidRemovedNode {
+                    node_id: NodeId::new(*removed_nodes.last().unwrap()),
+                }),
+                SplitterFlow::rebuild_from_topology(splitters(2), &topology).map(|_| ())
+            );
+        }
+    }
+
+    #[test]
+    fn reject_mismatching_number_of_nodes() {
+        let topology = sample_flow().topology();
+        assert_eq!(
+            Err(TopologyError::NodeCountMismatch {
+                expected: 3,
+                actual: 4,
+            }),
+            SplitterFlow::rebuild_from_topology(splitters(4), &topology).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn reject_invalid_connections() {
+        let mut topology = sample_flow().topology();
+        // Removed node
+        topology.connections.push((socket(1, 0), socket(2, 0)));
+        assert_eq!(
+            Err(TopologyError::Connect(ConnectError::UnknownNode {
+                node_id: NodeId::new(2),
+            })),
+            SplitterFlow::rebuild_from_topology(splitters(3), &topology).map(|_| ())
+        );
+        // Unknown node
+        topology.connections.pop();
+        topology.connections.push((socket(1, 0), socket(4, 0)));
+        assert_eq!(
+            Err(TopologyError::Connect(ConnectError::UnknownNode {
+                node_id: NodeId::new(4),
+            })),
+            SplitterFlow::rebuild_from_topology(splitters(3), &topology).map(|_| ())
+        );
+        // Unknown port
+        topology.connections.pop();
+        topology.connections.push((socket(1, 2), socket(3, 0)));
+        assert_eq!(
+            Err(TopologyError::Connect(ConnectError::PortIndexOutOfRange {
+                socket: socket(1, 2),
+                num_ports: 2,
+            })),
+            SplitterFlow::rebuild_from_topology(splitters(3), &topology).map(|_| ())
+        );
+    }
+
+    fn create_splitter(kind: &str, params: &NodeParams) -> Option<OneToManySplitter<(), i32>> {
+        if kind != "one-to-many-splitter" {
+            return None;
+        }
+        match params.get("num_outputs") {
+            Some(NodeParam::UInt(num_outputs)) => {
+                Some(OneToManySplitter::new(*num_outputs as usize))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn recreate_nodes_from_topology() {
+        let mut flow = sample_flow();
+        let splitter_id = flow.add_node(OneToManySplitter::new(3));
+        flow.connect(socket(1, 0), socket(splitter_id.into(), 0));
+        let topology = flow.topology_with_nodes();
+        assert_eq!(4, topology.nodes.len());
+        assert!(flow.topology().nodes.is_empty());
+        let recreated = SplitterFlow::from_topology(&topology, &create_splitter).unwrap();
+        assert_eq!(3, recreated.node(splitter_id).num_outputs());
+        assert_eq!(topology, recreated.topology_with_nodes());
+        let unsupported = |_: &str, _: &NodeParams| None;
+        assert_eq!(
+            Err(TopologyError::UnsupportedNode {
+                kind: "one-to-many-splitter".to_owned(),
+            }),
+            SplitterFlow::from_topology(&topology, &unsupported).map(|_| ())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn recreate_nodes_from_json() {
+        let mut flow = sample_flow();
+        let splitter_id = flow.add_node(OneToManySplitter::new(3));
+        flow.connect(socket(1, 0), socket(splitter_id.into(), 0));
+        let json = serde_json::to_string(&flow.topology_with_nodes()).unwrap();
+        let topology: FlowTopology = serde_json::from_str(&json).unwrap();
+        let recreated = SplitterFlow::from_topology(&topology, &create_splitter).unwrap();
+        assert_eq!(3, recreated.node(splitter_id).num_outputs());
+        assert_eq!(2, recreated.node(NodeId::new(0)).num_outputs());
+        assert_eq!(flow.topology_with_nodes(), recreated.topology_with_nodes());
+    }
+}
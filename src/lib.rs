@@ -1,6 +1,6 @@
 //#![deny(missing_docs)]
 //#![deny(missing_debug_implementations)]
-#![deny(intra_doc_link_resolution_failure)]
+#![deny(rustdoc::broken_intra_doc_links)]
 #![cfg_attr(test, deny(warnings))]
 
 //! # flowcalc
@@ -12,12 +12,23 @@
 /// A biased set of imports to ease usage of this crate.
 pub mod prelude;
 
+mod macros;
+
+// Used by exported macros
+#[doc(hidden)]
+pub use paste as __paste;
+
 pub mod flow;
 
 pub mod node;
 
 pub mod port;
 
+/// Utilities for testing
+///
+/// Inspect how flow graphs are executed.
+pub mod testing;
+
 // Restricts the visibility of trait methods
 #[derive(Debug)]
 struct SealedTag;
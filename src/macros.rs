@@ -0,0 +1,250 @@
+/// Define an enum of nodes with static dispatch
+///
+/// Generates an enum with a single tuple variant per node type
+/// and implements all node traits by delegating to the wrapped
+/// node. Additionally `From` is implemented for each variant.
+///
+/// This allows to mix different types of nodes in a single flow
+/// graph without the overhead of dynamic dispatch or shared
+/// ownership, e.g. when using `RcProxyNode`.
+///
+/// # Example
+///
+/// ```
+/// use flowcalc::node::{CalcNode, DebugPrinterSink, OneToManySplitter};
+///
+/// flowcalc::node_enum! {
+///     pub enum CalcFlowNode: Node<(), f64> {
+///         Splitter(OneToManySplitter<(), f64>),
+///         Calc(CalcNode<()>),
+///         Printer(DebugPrinterSink<(), f64>),
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! node_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident: Node<$ctrl:ty, $data:ty> {
+            $($variant:ident($node:ty)),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant($node)),+
+        }
+
+        $(
+            impl From<$node> for $name {
+                fn from(from: $node) -> Self {
+                    $name::$variant(from)
+                }
+            }
+        )+
+
+        impl $crate::node::Node<$ctrl, $data> for $name {}
+
+        impl $crate::node::NodeInputs<$ctrl, $data> for $name {
+            fn num_inputs(&self) -> usize {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeInputs::<$ctrl, $data>::num_inputs(node)),+
+                }
+            }
+
+            fn max_fan_in(&self, input_index: $crate::port::PortIndex) -> Option<usize> {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeInputs::<$ctrl, $data>::max_fan_in(node, input_index)),+
+                }
+            }
+
+            fn resize_inputs(&mut self, token: $crate::flow::AccessToken, num_inputs: usize) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeInputs::<$ctrl, $data>::resize_inputs(node, token, num_inputs)),+
+                }
+            }
+
+            fn accept_input_packet(
+                &mut self,
+                token: $crate::flow::AccessToken,
+                input_index: $crate::port::PortIndex,
+                packet: $crate::port::Packet<$data, $ctrl>,
+            ) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeInputs::<$ctrl, $data>::accept_input_packet(node, token, input_index, packet)),+
+                }
+            }
+
+            fn try_dispatch_input_packet(
+                &mut self,
+                token: $crate::flow::AccessToken,
+                input_index: $crate::port::PortIndex,
+            ) -> Option<$crate::port::Packet<$ctrl, $data>> {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeInputs::<$ctrl, $data>::try_dispatch_input_packet(node, token, input_index)),+
+                }
+            }
+
+            fn with_input_port_mut(
+                &mut self,
+                input_index: $crate::port::PortIndex,
+                f: &mut dyn FnMut(&mut $crate::port::Port<$data, $ctrl>),
+            ) -> bool {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeInputs::<$ctrl, $data>::with_input_port_mut(node, input_index, f)),+
+                }
+            }
+        }
+
+        impl $crate::node::NodeOutputs<$ctrl, $data> for $name {
+            fn num_outputs(&self) -> usize {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeOutputs::<$ctrl, $data>::num_outputs(node)),+
+                }
+            }
+
+            fn resize_outputs(&mut self, token: $crate::flow::AccessToken, num_outputs: usize) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeOutputs::<$ctrl, $data>::resize_outputs(node, token, num_outputs)),+
+                }
+            }
+
+            fn accept_output_packet(
+                &mut self,
+                token: $crate::flow::AccessToken,
+                output_index: $crate::port::PortIndex,
+                packet: $crate::port::Packet<$ctrl, $data>,
+            ) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeOutputs::<$ctrl, $data>::accept_output_packet(node, token, output_index, packet)),+
+                }
+            }
+
+            fn try_dispatch_output_packet(
+                &mut self,
+                token: $crate::flow::AccessToken,
+                output_index: $crate::port::PortIndex,
+            ) -> Option<$crate::port::Packet<$data, $ctrl>> {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeOutputs::<$ctrl, $data>::try_dispatch_output_packet(node, token, output_index)),+
+                }
+            }
+
+            fn reset_output_control(&mut self, token: $crate::flow::AccessToken, output_index: $crate::port::PortIndex) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeOutputs::<$ctrl, $data>::reset_output_control(node, token, output_index)),+
+                }
+            }
+
+            fn with_output_port_mut(
+                &mut self,
+                output_index: $crate::port::PortIndex,
+                f: &mut dyn FnMut(&mut $crate::port::Port<$ctrl, $data>),
+            ) -> bool {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeOutputs::<$ctrl, $data>::with_output_port_mut(node, output_index, f)),+
+                }
+            }
+        }
+
+        impl $crate::node::NodeProcessor for $name {
+            fn process_outputs(&mut self, token: $crate::flow::AccessToken) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeProcessor::process_outputs(node, token)),+
+                }
+            }
+
+            fn process_inputs(&mut self, token: $crate::flow::AccessToken) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeProcessor::process_inputs(node, token)),+
+                }
+            }
+
+            fn on_input_connected(&mut self, token: $crate::flow::AccessToken, input_index: $crate::port::PortIndex) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeProcessor::on_input_connected(node, token, input_index)),+
+                }
+            }
+
+            fn on_input_disconnected(&mut self, token: $crate::flow::AccessToken, input_index: $crate::port::PortIndex) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeProcessor::on_input_disconnected(node, token, input_index)),+
+                }
+            }
+
+            fn on_output_connected(&mut self, token: $crate::flow::AccessToken, output_index: $crate::port::PortIndex) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeProcessor::on_output_connected(node, token, output_index)),+
+                }
+            }
+
+            fn on_output_disconnected(&mut self, token: $crate::flow::AccessToken, output_index: $crate::port::PortIndex) {
+                match self {
+                    $($name::$variant(node) => $crate::node::NodeProcessor::on_output_disconnected(node, token, output_index)),+
+                }
+            }
+        }
+    };
+}
+
+/// Define named accessors for the ports of nodes with a fixed
+/// number of inputs and outputs
+///
+/// Generates a `const fn` for each port that returns the
+/// corresponding `PortIndex`, prefixed with `input_index_` or
+/// `output_index_`. Ports are numbered in order of appearance,
+/// starting at 0. Additionally the constants `NUM_INPUTS` and
+/// `NUM_OUTPUTS` are defined. The macro must be invoked within
+/// an `impl` block of the node.
+///
+/// # Example
+///
+/// ```
+/// use flowcalc::port::PortIndex;
+///
+/// struct AddNode;
+///
+/// impl AddNode {
+///     flowcalc::define_ports! {
+///         inputs: [lhs, rhs],
+///         outputs: [sum],
+///     }
+/// }
+///
+/// assert_eq!(2, AddNode::NUM_INPUTS);
+/// assert_eq!(1, AddNode::NUM_OUTPUTS);
+/// assert_eq!(PortIndex::new(1), AddNode::input_index_rhs());
+/// assert_eq!(PortIndex::new(0), AddNode::output_index_sum());
+/// ```
+#[macro_export]
+macro_rules! define_ports {
+    (
+        inputs: [$($input:ident),* $(,)?],
+        outputs: [$($output:ident),* $(,)?] $(,)?
+    ) => {
+        /// The number of input ports
+        pub const NUM_INPUTS: usize = $crate::define_ports!(@count $($input)*);
+
+        /// The number of output ports
+        pub const NUM_OUTPUTS: usize = $crate::define_ports!(@count $($output)*);
+
+        $crate::define_ports!(@index input_index_, 0usize; $($input)*);
+
+        $crate::define_ports!(@index output_index_, 0usize; $($output)*);
+    };
+    (@count) => {
+        0usize
+    };
+    (@count $head:ident $($tail:ident)*) => {
+        1usize + $crate::define_ports!(@count $($tail)*)
+    };
+    (@index $prefix:ident, $index:expr;) => {};
+    (@index $prefix:ident, $index:expr; $head:ident $($tail:ident)*) => {
+        $crate::__paste::paste! {
+            pub const fn [<$prefix $head>]() -> $crate::port::PortIndex {
+                $crate::port::PortIndex::new($index)
+            }
+        }
+
+        $crate::define_ports!(@index $prefix, $index + 1usize; $($tail)*);
+    };
+}
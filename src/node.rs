@@ -3,7 +3,21 @@ use super::{
     port::{Packet, Port, PortBay, PortIndex, VecPortBay},
 };
 
-use std::{cell::RefCell, fmt, rc::Rc};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+    hash::Hash,
+    ops::{Add, Sub},
+    rc::Rc,
+};
+
+#[cfg(feature = "num-traits")]
+mod integer;
+
+#[cfg(feature = "num-traits")]
+pub use self::integer::{IntegerCalcNode, IntegerOperation, OverflowArithmetic, OverflowPolicy};
 
 pub trait NodeProcessor {
     /// Backward pass: Refresh the state of all inputs
@@ -33,6 +47,26 @@ pub trait NodeProcessor {
     /// still be cached internally for subsequent operations, e.g.
     /// to determine if input values have changed between invocations.
     fn process_inputs(&mut self, token: AccessToken);
+
+    /// An input has been connected to an output of a preceding node
+    ///
+    /// Does nothing by default.
+    fn on_input_connected(&mut self, _token: AccessToken, _input_index: PortIndex) {}
+
+    /// An input has been disconnected from an output of a preceding node
+    ///
+    /// Does nothing by default.
+    fn on_input_disconnected(&mut self, _token: AccessToken, _input_index: PortIndex) {}
+
+    /// An output has been connected to an input of a subsequent node
+    ///
+    /// Does nothing by default.
+    fn on_output_connected(&mut self, _token: AccessToken, _output_index: PortIndex) {}
+
+    /// An output has been disconnected from an input of a subsequent node
+    ///
+    /// Does nothing by default.
+    fn on_output_disconnected(&mut self, _token: AccessToken, _output_index: PortIndex) {}
 }
 
 /// The input ports of a node
@@ -46,6 +80,29 @@ pub trait NodeInputs<C, D> {
     /// Query the number of input ports
     fn num_inputs(&self) -> usize;
 
+    /// Query the maximum number of outputs that could be
+    /// connected to an input port
+    ///
+    /// By default each input accepts only a single connection.
+    /// Nodes could override this method to reject all connections
+    /// with `Some(0)`. Flow graphs reject connections that exceed
+    /// this limit with `ConnectError::FanInExceeded`. They store
+    /// only a single connection per input, i.e. larger limits
+    /// including an unlimited number of connections with `None`
+    /// are capped.
+    fn max_fan_in(&self, _input_index: PortIndex) -> Option<usize> {
+        Some(1)
+    }
+
+    /// Change the number of input ports
+    ///
+    /// Invoked by the flow graph after all connections of surplus
+    /// inputs have been removed. Nodes with a fixed number of inputs
+    /// don't need to implement this method and panic by default.
+    fn resize_inputs(&mut self, _token: AccessToken, _num_inputs: usize) {
+        panic!("The number of inputs cannot be changed");
+    }
+
     fn accept_input_packet(
         &mut self,
         token: AccessToken,
@@ -58,6 +115,21 @@ pub trait NodeInputs<C, D> {
         token: AccessToken,
         input_index: PortIndex,
     ) -> Option<Packet<C, D>>;
+
+    /// Access an input port for inspection or modification
+    ///
+    /// Invokes the closure with the port and returns `true`. Returns
+    /// `false` without invoking the closure if the port is either not
+    /// accessible or doesn't exist.
+    ///
+    /// Not accessible by default.
+    fn with_input_port_mut(
+        &mut self,
+        _input_index: PortIndex,
+        _f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        false
+    }
 }
 
 /// The output ports of a node
@@ -71,6 +143,15 @@ pub trait NodeOutputs<C, D> {
     /// Query the number of output ports
     fn num_outputs(&self) -> usize;
 
+    /// Change the number of output ports
+    ///
+    /// Invoked by the flow graph after all connections of surplus
+    /// outputs have been removed. Nodes with a fixed number of outputs
+    /// don't need to implement this method and panic by default.
+    fn resize_outputs(&mut self, _token: AccessToken, _num_outputs: usize) {
+        panic!("The number of outputs cannot be changed");
+    }
+
     fn accept_output_packet(
         &mut self,
         token: AccessToken,
@@ -83,9 +164,129 @@ pub trait NodeOutputs<C, D> {
         token: AccessToken,
         output_index: PortIndex,
     ) -> Option<Packet<D, C>>;
+
+    /// Discard the control of an output port
+    ///
+    /// Invoked by the flow graph after the output has been
+    /// disconnected to prevent that a stale control continues
+    /// to activate the node during subsequent backward passes.
+    ///
+    /// Resets the control through `with_output_port_mut()` by
+    /// default. Nodes that don't provide access to their output
+    /// ports need to override this method.
+    fn reset_output_control(&mut self, _token: AccessToken, output_index: PortIndex) {
+        self.with_output_port_mut(output_index, &mut |port| port.incoming = None);
+    }
+
+    /// Access an output port for inspection or modification
+    ///
+    /// Invokes the closure with the port and returns `true`. Returns
+    /// `false` without invoking the closure if the port is either not
+    /// accessible or doesn't exist.
+    ///
+    /// Not accessible by default.
+    fn with_output_port_mut(
+        &mut self,
+        _output_index: PortIndex,
+        _f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        false
+    }
+}
+
+/// Dynamic typing of nodes
+///
+/// Implemented for all types with a `'static` lifetime.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T> AsAny for T
+where
+    T: Any,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A single configuration parameter of a node
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NodeParam {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Text(String),
+}
+
+/// Named configuration parameters of a node
+pub type NodeParams = BTreeMap<String, NodeParam>;
+
+/// Identification of nodes for serialization
+///
+/// The kind is a stable name of the node type. Together with the
+/// parameters it should provide all information that is needed to
+/// reconstruct an equivalent node in its initial state.
+pub trait NodeKind {
+    /// A stable name of the node type
+    fn kind(&self) -> &'static str;
+
+    /// The parameters that are needed to reconstruct the node
+    ///
+    /// Empty by default for nodes without any parameters.
+    fn params(&self) -> NodeParams {
+        NodeParams::new()
+    }
+}
+
+impl<N> NodeKind for Box<N>
+where
+    N: NodeKind + ?Sized,
+{
+    fn kind(&self) -> &'static str {
+        (**self).kind()
+    }
+
+    fn params(&self) -> NodeParams {
+        (**self).params()
+    }
+}
+
+/// Reconstruction of nodes from their kind and parameters
+///
+/// The counterpart of `NodeKind`. Implemented for all closures
+/// with a matching signature.
+pub trait NodeFactory<N> {
+    /// Create a node in its initial state
+    ///
+    /// Returns `None` if either the kind is unknown or if the
+    /// parameters are invalid.
+    fn create_node(&self, kind: &str, params: &NodeParams) -> Option<N>;
+}
+
+impl<N, F> NodeFactory<N> for F
+where
+    F: Fn(&str, &NodeParams) -> Option<N>,
+{
+    fn create_node(&self, kind: &str, params: &NodeParams) -> Option<N> {
+        self(kind, params)
+    }
 }
 
-pub trait Node<C, D>: NodeInputs<C, D> + NodeOutputs<C, D> + NodeProcessor {}
+/// A processing node in a flow graph
+///
+/// Nodes must not contain any non-`'static` references, i.e. the
+/// node type must satisfy the `'static` lifetime bound. This allows
+/// to downcast nodes into their concrete type at runtime.
+pub trait Node<C, D>: NodeInputs<C, D> + NodeOutputs<C, D> + NodeProcessor + AsAny {}
 
 /// A reference-counted node proxy
 #[derive(Clone)]
@@ -101,13 +302,26 @@ impl<C, D> RcProxyNode<C, D> {
     }
 }
 
-impl<C, D> Node<C, D> for RcProxyNode<C, D> {}
+impl<C, D> Node<C, D> for RcProxyNode<C, D>
+where
+    C: 'static,
+    D: 'static,
+{
+}
 
 impl<C, D> NodeInputs<C, D> for RcProxyNode<C, D> {
     fn num_inputs(&self) -> usize {
         self.node.borrow().num_inputs()
     }
 
+    fn max_fan_in(&self, input_index: PortIndex) -> Option<usize> {
+        self.node.borrow().max_fan_in(input_index)
+    }
+
+    fn resize_inputs(&mut self, token: AccessToken, num_inputs: usize) {
+        self.node.borrow_mut().resize_inputs(token, num_inputs)
+    }
+
     fn accept_input_packet(
         &mut self,
         token: AccessToken,
@@ -128,6 +342,14 @@ impl<C, D> NodeInputs<C, D> for RcProxyNode<C, D> {
             .borrow_mut()
             .try_dispatch_input_packet(token, input_index)
     }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.node.borrow_mut().with_input_port_mut(input_index, f)
+    }
 }
 
 impl<C, D> NodeOutputs<C, D> for RcProxyNode<C, D> {
@@ -135,6 +357,10 @@ impl<C, D> NodeOutputs<C, D> for RcProxyNode<C, D> {
         self.node.borrow().num_outputs()
     }
 
+    fn resize_outputs(&mut self, token: AccessToken, num_outputs: usize) {
+        self.node.borrow_mut().resize_outputs(token, num_outputs)
+    }
+
     fn accept_output_packet(
         &mut self,
         token: AccessToken,
@@ -155,6 +381,20 @@ impl<C, D> NodeOutputs<C, D> for RcProxyNode<C, D> {
             .borrow_mut()
             .try_dispatch_output_packet(token, output_index)
     }
+
+    fn reset_output_control(&mut self, token: AccessToken, output_index: PortIndex) {
+        self.node
+            .borrow_mut()
+            .reset_output_control(token, output_index)
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        self.node.borrow_mut().with_output_port_mut(output_index, f)
+    }
 }
 
 impl<C, D> NodeProcessor for RcProxyNode<C, D> {
@@ -165,6 +405,150 @@ impl<C, D> NodeProcessor for RcProxyNode<C, D> {
     fn process_inputs(&mut self, token: AccessToken) {
         self.node.borrow_mut().process_inputs(token);
     }
+
+    fn on_input_connected(&mut self, token: AccessToken, input_index: PortIndex) {
+        self.node
+            .borrow_mut()
+            .on_input_connected(token, input_index);
+    }
+
+    fn on_input_disconnected(&mut self, token: AccessToken, input_index: PortIndex) {
+        self.node
+            .borrow_mut()
+            .on_input_disconnected(token, input_index);
+    }
+
+    fn on_output_connected(&mut self, token: AccessToken, output_index: PortIndex) {
+        self.node
+            .borrow_mut()
+            .on_output_connected(token, output_index);
+    }
+
+    fn on_output_disconnected(&mut self, token: AccessToken, output_index: PortIndex) {
+        self.node
+            .borrow_mut()
+            .on_output_disconnected(token, output_index);
+    }
+}
+
+/// Boxed nodes, e.g. trait objects for flow graphs with
+/// heterogeneous nodes
+///
+/// Please note that downcasting a boxed node with `as_any()`
+/// yields the box itself and not the boxed node.
+impl<C, D, N> Node<C, D> for Box<N> where N: Node<C, D> + ?Sized + 'static {}
+
+impl<C, D, N> NodeInputs<C, D> for Box<N>
+where
+    N: NodeInputs<C, D> + ?Sized,
+{
+    fn num_inputs(&self) -> usize {
+        (**self).num_inputs()
+    }
+
+    fn max_fan_in(&self, input_index: PortIndex) -> Option<usize> {
+        (**self).max_fan_in(input_index)
+    }
+
+    fn resize_inputs(&mut self, token: AccessToken, num_inputs: usize) {
+        (**self).resize_inputs(token, num_inputs)
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        (**self).accept_input_packet(token, input_index, packet)
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        (**self).try_dispatch_input_packet(token, input_index)
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        (**self).with_input_port_mut(input_index, f)
+    }
+}
+
+impl<C, D, N> NodeOutputs<C, D> for Box<N>
+where
+    N: NodeOutputs<C, D> + ?Sized,
+{
+    fn num_outputs(&self) -> usize {
+        (**self).num_outputs()
+    }
+
+    fn resize_outputs(&mut self, token: AccessToken, num_outputs: usize) {
+        (**self).resize_outputs(token, num_outputs)
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        token: AccessToken,
+        output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        (**self).accept_output_packet(token, output_index, packet)
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        token: AccessToken,
+        output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        (**self).try_dispatch_output_packet(token, output_index)
+    }
+
+    fn reset_output_control(&mut self, token: AccessToken, output_index: PortIndex) {
+        (**self).reset_output_control(token, output_index)
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        (**self).with_output_port_mut(output_index, f)
+    }
+}
+
+impl<N> NodeProcessor for Box<N>
+where
+    N: NodeProcessor + ?Sized,
+{
+    fn process_outputs(&mut self, token: AccessToken) {
+        (**self).process_outputs(token);
+    }
+
+    fn process_inputs(&mut self, token: AccessToken) {
+        (**self).process_inputs(token);
+    }
+
+    fn on_input_connected(&mut self, token: AccessToken, input_index: PortIndex) {
+        (**self).on_input_connected(token, input_index);
+    }
+
+    fn on_input_disconnected(&mut self, token: AccessToken, input_index: PortIndex) {
+        (**self).on_input_disconnected(token, input_index);
+    }
+
+    fn on_output_connected(&mut self, token: AccessToken, output_index: PortIndex) {
+        (**self).on_output_connected(token, output_index);
+    }
+
+    fn on_output_disconnected(&mut self, token: AccessToken, output_index: PortIndex) {
+        (**self).on_output_disconnected(token, output_index);
+    }
 }
 
 pub trait JoinablePortControl {
@@ -187,10 +571,41 @@ impl JoinablePortControl for bool {
     }
 }
 
-#[derive(Default, Debug, Clone)]
+/// Combines the values of multiple inputs into a single value
+///
+/// The dual of `JoinablePortControl` for the data that is
+/// propagated in forward direction.
+pub trait JoinablePortData {
+    fn join_next_port_data(self, next_data: Self) -> Self;
+}
+
+impl JoinablePortData for () {
+    fn join_next_port_data(self, _next_data: Self) -> Self {
+        self
+    }
+}
+
+/// Concatenation
+impl JoinablePortData for String {
+    fn join_next_port_data(mut self, next_data: Self) -> Self {
+        self.push_str(&next_data);
+        self
+    }
+}
+
+/// Concatenation
+impl<T> JoinablePortData for Vec<T> {
+    fn join_next_port_data(mut self, mut next_data: Self) -> Self {
+        self.append(&mut next_data);
+        self
+    }
+}
+
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct OneToManySplitter<C, D> {
     input: Port<D, C>,
     outputs: VecPortBay<C, D>,
+    enabled_outputs: Vec<bool>,
 }
 
 impl<C, D> OneToManySplitter<C, D> {
@@ -198,6 +613,7 @@ impl<C, D> OneToManySplitter<C, D> {
         Self {
             input: Port::new(),
             outputs: VecPortBay::new(num_outputs),
+            enabled_outputs: vec![true; num_outputs],
         }
     }
 
@@ -216,12 +632,56 @@ impl<C, D> OneToManySplitter<C, D> {
     pub fn output_mut(&mut self, output_index: PortIndex) -> &mut Port<C, D> {
         self.outputs.port_mut(output_index)
     }
+
+    /// Check if the input has been requested during the backward pass
+    pub fn is_input_requested(&self) -> bool {
+        self.input.outgoing.is_some()
+    }
+
+    /// Activate an output by setting its control
+    pub fn request_output(&mut self, output_index: PortIndex, ctrl: C) {
+        self.output_mut(output_index).incoming = Some(ctrl);
+    }
+
+    /// Check if an output has been activated
+    pub fn is_output_requested(&self, output_index: PortIndex) -> bool {
+        self.output(output_index).incoming.is_some()
+    }
+
+    /// Enable or disable an output
+    ///
+    /// Disabled outputs don't receive any values during the forward
+    /// pass and their control is ignored during the backward pass,
+    /// regardless of whether they have been activated or not. All
+    /// outputs are enabled initially.
+    pub fn set_output_enabled(&mut self, output_index: PortIndex, enabled: bool) {
+        self.enabled_outputs[usize::from(output_index)] = enabled;
+    }
+
+    pub fn is_output_enabled(&self, output_index: PortIndex) -> bool {
+        self.enabled_outputs[usize::from(output_index)]
+    }
+}
+
+impl<C, D> NodeKind for OneToManySplitter<C, D> {
+    fn kind(&self) -> &'static str {
+        "one-to-many-splitter"
+    }
+
+    fn params(&self) -> NodeParams {
+        let mut params = NodeParams::new();
+        params.insert(
+            "num_outputs".to_owned(),
+            NodeParam::UInt(self.outputs.num_ports() as u64),
+        );
+        params
+    }
 }
 
 impl<C, D> Node<C, D> for OneToManySplitter<C, D>
 where
-    C: Clone + JoinablePortControl,
-    D: Clone,
+    C: Clone + JoinablePortControl + 'static,
+    D: Clone + 'static,
 {
 }
 
@@ -252,6 +712,18 @@ where
         debug_assert_eq!(PortIndex::new(0), _input_index);
         self.input.try_dispatch_packet()
     }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        if input_index != 0 {
+            return false;
+        }
+        f(&mut self.input);
+        true
+    }
 }
 
 impl<C, D> NodeOutputs<C, D> for OneToManySplitter<C, D>
@@ -263,6 +735,11 @@ where
         self.outputs.num_ports()
     }
 
+    fn resize_outputs(&mut self, _token: AccessToken, num_outputs: usize) {
+        self.outputs.resize_ports(num_outputs);
+        self.enabled_outputs.resize(num_outputs, true);
+    }
+
     fn accept_output_packet(
         &mut self,
         _token: AccessToken,
@@ -279,6 +756,14 @@ where
     ) -> Option<Packet<D, C>> {
         self.outputs.try_dispatch_packet(output_index)
     }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        self.outputs.get_port_mut(output_index).map(f).is_some()
+    }
 }
 
 impl<C, D> NodeProcessor for OneToManySplitter<C, D>
@@ -287,39 +772,53 @@ where
     D: Clone,
 {
     fn process_inputs(&mut self, _token: AccessToken) {
-        for output_port in self.outputs.ports_mut() {
+        for (output_port, enabled) in self
+            .outputs
+            .ports_mut()
+            .zip(self.enabled_outputs.iter().copied())
+        {
+            if !enabled {
+                output_port.outgoing = None;
+                continue;
+            }
             if output_port.incoming.is_none() {
                 continue;
             }
-            output_port.outgoing = self
-                .input
-                .incoming
-                .as_ref()
-                .map(|incoming| incoming.clone());
+            output_port.outgoing = self.input.incoming.clone();
         }
     }
 
     fn process_outputs(&mut self, _: AccessToken) {
-        self.input.outgoing = self.outputs.ports().fold(None, |ctrl, port| {
-            if let Some(ctrl) = ctrl {
-                Some(ctrl.join_next_port_control(port.incoming.as_ref()))
-            } else {
-                port.incoming.as_ref().map(Clone::clone)
-            }
-        })
+        self.input.outgoing = self
+            .outputs
+            .ports()
+            .zip(self.enabled_outputs.iter().copied())
+            .filter(|(_, enabled)| *enabled)
+            .fold(None, |ctrl, (port, _)| {
+                if let Some(ctrl) = ctrl {
+                    Some(ctrl.join_next_port_control(port.incoming.as_ref()))
+                } else {
+                    port.incoming.clone()
+                }
+            })
     }
 }
 
-#[derive(Debug, Default, Clone)]
-
-pub struct DebugPrinterSink<C, D> {
-    pub inputs: VecPortBay<D, C>,
+/// Joins the values of all inputs into a single output
+///
+/// Inputs without a value are skipped. All inputs are requested
+/// with the control of the output.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct ManyToOneJoiner<C, D> {
+    inputs: VecPortBay<D, C>,
+    output: Port<C, D>,
 }
 
-impl<C, D> DebugPrinterSink<C, D> {
+impl<C, D> ManyToOneJoiner<C, D> {
     pub fn new(num_inputs: usize) -> Self {
         Self {
             inputs: VecPortBay::new(num_inputs),
+            output: Port::new(),
         }
     }
 
@@ -330,18 +829,284 @@ impl<C, D> DebugPrinterSink<C, D> {
     pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<D, C> {
         self.inputs.port_mut(input_index)
     }
-}
 
-impl<C, D> Node<C, D> for DebugPrinterSink<C, D> where D: fmt::Debug {}
+    pub fn output(&self) -> &Port<C, D> {
+        &self.output
+    }
 
-impl<C, D> NodeInputs<C, D> for DebugPrinterSink<C, D>
-where
-    D: fmt::Debug,
-{
-    fn num_inputs(&self) -> usize {
+    pub fn output_mut(&mut self) -> &mut Port<C, D> {
+        &mut self.output
+    }
+
+    /// Check if an input has been requested during the backward pass
+    pub fn is_input_requested(&self, input_index: PortIndex) -> bool {
+        self.input(input_index).outgoing.is_some()
+    }
+
+    /// Activate the output by setting its control
+    pub fn request_output(&mut self, ctrl: C) {
+        self.output.incoming = Some(ctrl);
+    }
+
+    /// Check if the output has been activated
+    pub fn is_output_requested(&self) -> bool {
+        self.output.incoming.is_some()
+    }
+}
+
+impl<C, D> NodeKind for ManyToOneJoiner<C, D> {
+    fn kind(&self) -> &'static str {
+        "many-to-one-joiner"
+    }
+
+    fn params(&self) -> NodeParams {
+        let mut params = NodeParams::new();
+        params.insert(
+            "num_inputs".to_owned(),
+            NodeParam::UInt(self.inputs.num_ports() as u64),
+        );
+        params
+    }
+}
+
+impl<C, D> Node<C, D> for ManyToOneJoiner<C, D>
+where
+    C: Clone + 'static,
+    D: JoinablePortData + 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for ManyToOneJoiner<C, D>
+where
+    C: Clone,
+    D: JoinablePortData,
+{
+    fn num_inputs(&self) -> usize {
+        self.inputs.num_ports()
+    }
+
+    fn resize_inputs(&mut self, _token: AccessToken, num_inputs: usize) {
+        self.inputs.resize_ports(num_inputs);
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        self.inputs.accept_packet(input_index, packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        self.inputs.try_dispatch_packet(input_index)
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.inputs.get_port_mut(input_index).map(f).is_some()
+    }
+}
+
+impl<C, D> NodeOutputs<C, D> for ManyToOneJoiner<C, D>
+where
+    C: Clone,
+    D: JoinablePortData,
+{
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.try_dispatch_packet()
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        if output_index != 0 {
+            return false;
+        }
+        f(&mut self.output);
+        true
+    }
+}
+
+impl<C, D> NodeProcessor for ManyToOneJoiner<C, D>
+where
+    C: Clone,
+    D: JoinablePortData,
+{
+    fn process_inputs(&mut self, _token: AccessToken) {
+        // Inactive inputs without a value are skipped
+        let joined = self
+            .inputs
+            .ports_mut()
+            .filter_map(|port| port.incoming.take())
+            .fold(None, |joined, value| {
+                if let Some(joined) = joined {
+                    Some(D::join_next_port_data(joined, value))
+                } else {
+                    Some(value)
+                }
+            });
+        if self.output.incoming.is_none() {
+            self.output.outgoing = None;
+            return;
+        }
+        self.output.outgoing = joined;
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        for port in self.inputs.ports_mut() {
+            port.outgoing = self.output.incoming.clone();
+        }
+    }
+}
+
+/// A sink that prints the values of all inputs
+///
+/// By default the values are printed on every forward pass. The
+/// output could be throttled by configuring a print interval, i.e.
+/// the values are only printed on every n-th pass. Input values
+/// are consumed on each pass, regardless of whether they are
+/// printed or not.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DebugPrinterSink<C, D> {
+    pub inputs: VecPortBay<D, C>,
+    print_interval: usize,
+    pass_count: usize,
+}
+
+impl<C, D> Default for DebugPrinterSink<C, D> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<C, D> DebugPrinterSink<C, D> {
+    pub fn new(num_inputs: usize) -> Self {
+        Self {
+            inputs: VecPortBay::new(num_inputs),
+            print_interval: 1,
+            pass_count: 0,
+        }
+    }
+
+    pub fn print_interval(&self) -> usize {
+        self.print_interval
+    }
+
+    /// Only print the input values on every n-th pass
+    ///
+    /// The values of the first pass are always printed. An
+    /// interval of 0 disables printing entirely.
+    pub fn set_print_interval(&mut self, print_interval: usize) {
+        self.print_interval = print_interval;
+        self.pass_count = 0;
+    }
+
+    pub fn input(&self, input_index: PortIndex) -> &Port<D, C> {
+        self.inputs.port(input_index)
+    }
+
+    pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<D, C> {
+        self.inputs.port_mut(input_index)
+    }
+
+    /// Activate an input by setting its control
+    pub fn request_input(&mut self, input_index: PortIndex, ctrl: C) {
+        self.input_mut(input_index).outgoing = Some(ctrl);
+    }
+
+    /// Activate all inputs by setting their control
+    pub fn request_all_inputs(&mut self, ctrl: C)
+    where
+        C: Clone,
+    {
+        for port in self.inputs.ports_mut() {
+            port.outgoing = Some(ctrl.clone());
+        }
+    }
+
+    /// Check if an input has been activated
+    pub fn is_input_requested(&self, input_index: PortIndex) -> bool {
+        self.input(input_index).outgoing.is_some()
+    }
+
+    /// Count a pass and decide if its values are printed
+    fn count_pass(&mut self) -> bool {
+        if self.print_interval == 0 {
+            return false;
+        }
+        let print = self.pass_count == 0;
+        self.pass_count = (self.pass_count + 1) % self.print_interval;
+        print
+    }
+}
+
+impl<C, D> NodeKind for DebugPrinterSink<C, D> {
+    fn kind(&self) -> &'static str {
+        "debug-printer-sink"
+    }
+
+    fn params(&self) -> NodeParams {
+        let mut params = NodeParams::new();
+        params.insert(
+            "num_inputs".to_owned(),
+            NodeParam::UInt(self.inputs.num_ports() as u64),
+        );
+        params.insert(
+            "print_interval".to_owned(),
+            NodeParam::UInt(self.print_interval as u64),
+        );
+        params
+    }
+}
+
+impl<C, D> Node<C, D> for DebugPrinterSink<C, D>
+where
+    C: 'static,
+    D: fmt::Debug + 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for DebugPrinterSink<C, D>
+where
+    D: fmt::Debug,
+{
+    fn num_inputs(&self) -> usize {
         self.inputs.num_ports()
     }
 
+    fn resize_inputs(&mut self, _token: AccessToken, num_inputs: usize) {
+        self.inputs.resize_ports(num_inputs);
+    }
+
     fn accept_input_packet(
         &mut self,
         _token: AccessToken,
@@ -358,6 +1123,14 @@ where
     ) -> Option<Packet<C, D>> {
         self.inputs.try_dispatch_packet(input_index)
     }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.inputs.get_port_mut(input_index).map(f).is_some()
+    }
 }
 
 impl<C, D> NodeOutputs<C, D> for DebugPrinterSink<C, D> {
@@ -389,16 +1162,2499 @@ where
 {
     fn process_inputs(&mut self, _: AccessToken) {
         // No outputs, just a side-effect
-        println!(
-            "{:?}",
-            self.inputs
-                .ports_mut()
-                .map(|port| port.incoming.as_ref())
-                .collect::<Vec<_>>()
+        if self.count_pass() {
+            println!(
+                "{:?}",
+                self.inputs
+                    .ports_mut()
+                    .map(|port| port.incoming.take())
+                    .collect::<Vec<_>>()
+            );
+        } else {
+            for port in self.inputs.ports_mut() {
+                port.incoming = None;
+            }
+        }
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        // No outputs, nothing to do
+    }
+}
+
+/// A sink that retains only the most recent values of all inputs
+///
+/// Each input has a fixed-capacity history. When the history
+/// is full the oldest value is discarded for each new value.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RingBufferSink<C, D> {
+    inputs: VecPortBay<D, C>,
+    recent: Vec<VecDeque<D>>,
+    capacity: usize,
+}
+
+impl<C, D> RingBufferSink<C, D> {
+    pub fn with_capacity(num_inputs: usize, capacity: usize) -> Self {
+        Self {
+            inputs: VecPortBay::new(num_inputs),
+            recent: std::iter::repeat_with(|| VecDeque::with_capacity(capacity))
+                .take(num_inputs)
+                .collect(),
+            capacity,
+        }
+    }
+
+    /// The maximum number of values that are retained per input
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The most recent values of an input, ordered from oldest
+    /// to newest
+    pub fn recent(&self, input_index: PortIndex) -> &VecDeque<D> {
+        &self.recent[usize::from(input_index)]
+    }
+
+    /// Discard the recent values of all inputs
+    pub fn clear(&mut self) {
+        for recent in self.recent.iter_mut() {
+            recent.clear();
+        }
+    }
+
+    pub fn input(&self, input_index: PortIndex) -> &Port<D, C> {
+        self.inputs.port(input_index)
+    }
+
+    pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<D, C> {
+        self.inputs.port_mut(input_index)
+    }
+
+    /// Activate an input by setting its control
+    pub fn request_input(&mut self, input_index: PortIndex, ctrl: C) {
+        self.input_mut(input_index).outgoing = Some(ctrl);
+    }
+
+    /// Activate all inputs by setting their control
+    pub fn request_all_inputs(&mut self, ctrl: C)
+    where
+        C: Clone,
+    {
+        for port in self.inputs.ports_mut() {
+            port.outgoing = Some(ctrl.clone());
+        }
+    }
+
+    /// Check if an input has been activated
+    pub fn is_input_requested(&self, input_index: PortIndex) -> bool {
+        self.input(input_index).outgoing.is_some()
+    }
+}
+
+impl<C, D> NodeKind for RingBufferSink<C, D> {
+    fn kind(&self) -> &'static str {
+        "ring-buffer-sink"
+    }
+
+    fn params(&self) -> NodeParams {
+        let mut params = NodeParams::new();
+        params.insert(
+            "num_inputs".to_owned(),
+            NodeParam::UInt(self.inputs.num_ports() as u64),
         );
+        params.insert("capacity".to_owned(), NodeParam::UInt(self.capacity as u64));
+        params
+    }
+}
+
+impl<C, D> Node<C, D> for RingBufferSink<C, D>
+where
+    C: 'static,
+    D: 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for RingBufferSink<C, D> {
+    fn num_inputs(&self) -> usize {
+        self.inputs.num_ports()
+    }
+
+    fn resize_inputs(&mut self, _token: AccessToken, num_inputs: usize) {
+        self.inputs.resize_ports(num_inputs);
+        let capacity = self.capacity;
+        self.recent
+            .resize_with(num_inputs, || VecDeque::with_capacity(capacity));
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        self.inputs.accept_packet(input_index, packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        self.inputs.try_dispatch_packet(input_index)
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.inputs.get_port_mut(input_index).map(f).is_some()
+    }
+}
+
+impl<C, D> NodeOutputs<C, D> for RingBufferSink<C, D> {
+    fn num_outputs(&self) -> usize {
+        0
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        _packet: Packet<C, D>,
+    ) {
+        unimplemented!();
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        unimplemented!();
+    }
+}
+
+impl<C, D> NodeProcessor for RingBufferSink<C, D> {
+    fn process_inputs(&mut self, _: AccessToken) {
+        for (port, recent) in self.inputs.ports_mut().zip(self.recent.iter_mut()) {
+            if let Some(value) = port.incoming.take() {
+                if self.capacity == 0 {
+                    continue;
+                }
+                if recent.len() == self.capacity {
+                    recent.pop_front();
+                }
+                recent.push_back(value);
+            }
+        }
     }
 
     fn process_outputs(&mut self, _: AccessToken) {
         // No outputs, nothing to do
     }
 }
+
+/// A source node that generates values with a closure
+///
+/// The closure is invoked once during each forward pass
+/// while the single output is active.
+#[allow(missing_debug_implementations)]
+pub struct FnSource<C, D, F> {
+    output: Port<C, D>,
+    generate: F,
+}
+
+impl<C, D, F> FnSource<C, D, F>
+where
+    F: FnMut() -> D,
+{
+    pub fn new(generate: F) -> Self {
+        Self {
+            output: Port::new(),
+            generate,
+        }
+    }
+
+    pub fn output(&self) -> &Port<C, D> {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut Port<C, D> {
+        &mut self.output
+    }
+
+    /// Activate the output by setting its control
+    pub fn request_output(&mut self, ctrl: C) {
+        self.output.incoming = Some(ctrl);
+    }
+
+    /// Check if the output has been activated
+    pub fn is_output_requested(&self) -> bool {
+        self.output.incoming.is_some()
+    }
+}
+
+/// Create a source node from a closure
+pub fn source_fn<C, D, F>(generate: F) -> FnSource<C, D, F>
+where
+    F: FnMut() -> D,
+{
+    FnSource::new(generate)
+}
+
+impl<C, D, F> Node<C, D> for FnSource<C, D, F>
+where
+    C: 'static,
+    D: 'static,
+    F: FnMut() -> D + 'static,
+{
+}
+
+impl<C, D, F> NodeInputs<C, D> for FnSource<C, D, F>
+where
+    F: FnMut() -> D,
+{
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        _packet: Packet<D, C>,
+    ) {
+        unimplemented!();
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        unimplemented!();
+    }
+}
+
+impl<C, D, F> NodeOutputs<C, D> for FnSource<C, D, F>
+where
+    F: FnMut() -> D,
+{
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.try_dispatch_packet()
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        if output_index != 0 {
+            return false;
+        }
+        f(&mut self.output);
+        true
+    }
+}
+
+impl<C, D, F> NodeProcessor for FnSource<C, D, F>
+where
+    F: FnMut() -> D,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        if self.output.incoming.is_none() {
+            self.output.outgoing = None;
+            return;
+        }
+        self.output.outgoing = Some((self.generate)());
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        // No inputs, nothing to do
+    }
+}
+
+/// A node that computes the values of its outputs with a closure
+///
+/// During the forward pass the closure receives the incoming
+/// values of all inputs and fills in the values of the outputs.
+/// It is only invoked while at least one output is active and
+/// values of inactive outputs are discarded.
+///
+/// The inputs are requested by joining the controls of all outputs.
+#[allow(missing_debug_implementations)]
+pub struct FnNode<C, D, F> {
+    inputs: VecPortBay<D, C>,
+    outputs: VecPortBay<C, D>,
+    input_values: Vec<Option<D>>,
+    output_values: Vec<Option<D>>,
+    compute: F,
+}
+
+impl<C, D, F> FnNode<C, D, F>
+where
+    F: FnMut(&[Option<D>], &mut [Option<D>]),
+{
+    pub fn new(num_inputs: usize, num_outputs: usize, compute: F) -> Self {
+        Self {
+            inputs: VecPortBay::new(num_inputs),
+            outputs: VecPortBay::new(num_outputs),
+            input_values: Vec::with_capacity(num_inputs),
+            output_values: Vec::with_capacity(num_outputs),
+            compute,
+        }
+    }
+
+    pub fn input(&self, input_index: PortIndex) -> &Port<D, C> {
+        self.inputs.port(input_index)
+    }
+
+    pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<D, C> {
+        self.inputs.port_mut(input_index)
+    }
+
+    pub fn output(&self, output_index: PortIndex) -> &Port<C, D> {
+        self.outputs.port(output_index)
+    }
+
+    pub fn output_mut(&mut self, output_index: PortIndex) -> &mut Port<C, D> {
+        self.outputs.port_mut(output_index)
+    }
+
+    /// Activate an output by setting its control
+    pub fn request_output(&mut self, output_index: PortIndex, ctrl: C) {
+        self.output_mut(output_index).incoming = Some(ctrl);
+    }
+
+    /// Check if an output has been activated
+    pub fn is_output_requested(&self, output_index: PortIndex) -> bool {
+        self.output(output_index).incoming.is_some()
+    }
+}
+
+impl<C, D, F> Node<C, D> for FnNode<C, D, F>
+where
+    C: Clone + JoinablePortControl + 'static,
+    D: 'static,
+    F: FnMut(&[Option<D>], &mut [Option<D>]) + 'static,
+{
+}
+
+impl<C, D, F> NodeInputs<C, D> for FnNode<C, D, F>
+where
+    F: FnMut(&[Option<D>], &mut [Option<D>]),
+{
+    fn num_inputs(&self) -> usize {
+        self.inputs.num_ports()
+    }
+
+    fn resize_inputs(&mut self, _token: AccessToken, num_inputs: usize) {
+        self.inputs.resize_ports(num_inputs);
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        self.inputs.accept_packet(input_index, packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        self.inputs.try_dispatch_packet(input_index)
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.inputs.get_port_mut(input_index).map(f).is_some()
+    }
+}
+
+impl<C, D, F> NodeOutputs<C, D> for FnNode<C, D, F>
+where
+    F: FnMut(&[Option<D>], &mut [Option<D>]),
+{
+    fn num_outputs(&self) -> usize {
+        self.outputs.num_ports()
+    }
+
+    fn resize_outputs(&mut self, _token: AccessToken, num_outputs: usize) {
+        self.outputs.resize_ports(num_outputs);
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        self.outputs.accept_packet(output_index, packet)
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        self.outputs.try_dispatch_packet(output_index)
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        self.outputs.get_port_mut(output_index).map(f).is_some()
+    }
+}
+
+impl<C, D, F> NodeProcessor for FnNode<C, D, F>
+where
+    C: Clone + JoinablePortControl,
+    F: FnMut(&[Option<D>], &mut [Option<D>]),
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        // Input values are consumed even if no output is active
+        self.input_values.clear();
+        self.input_values
+            .extend(self.inputs.ports_mut().map(|port| port.incoming.take()));
+        if self.outputs.ports().all(|port| port.incoming.is_none()) {
+            for output_port in self.outputs.ports_mut() {
+                output_port.outgoing = None;
+            }
+            return;
+        }
+        // The buffers are reused to avoid allocations on each pass
+        self.output_values.clear();
+        self.output_values
+            .resize_with(self.outputs.num_ports(), Default::default);
+        (self.compute)(&self.input_values, &mut self.output_values);
+        for (output_port, value) in self.outputs.ports_mut().zip(self.output_values.drain(..)) {
+            output_port.outgoing = if output_port.incoming.is_some() {
+                value
+            } else {
+                None
+            };
+        }
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        let ctrl: Option<C> = self.outputs.ports().fold(None, |ctrl, port| {
+            if let Some(ctrl) = ctrl {
+                Some(ctrl.join_next_port_control(port.incoming.as_ref()))
+            } else {
+                port.incoming.clone()
+            }
+        });
+        for port in self.inputs.ports_mut() {
+            port.outgoing = ctrl.clone();
+        }
+    }
+}
+
+/// A source node that generates a sequence of integers
+///
+/// Starting at `start` the counter is incremented by `step` after
+/// each value that has been generated. The counter wraps around at
+/// an optional `modulus`, otherwise at `u64::MAX`.
+///
+/// A single value is generated during each forward pass while
+/// the output is active.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CounterSource<C, D> {
+    output: Port<C, D>,
+    start: u64,
+    step: u64,
+    modulus: Option<u64>,
+    next: u64,
+}
+
+impl<C, D> CounterSource<C, D> {
+    /// Create a new counter
+    ///
+    /// Panics if the `modulus` is 0.
+    pub fn new(start: u64, step: u64, modulus: Option<u64>) -> Self {
+        assert_ne!(Some(0), modulus, "modulus must not be 0");
+        let start = modulus.map_or(start, |modulus| start % modulus);
+        Self {
+            output: Port::new(),
+            start,
+            step,
+            modulus,
+            next: start,
+        }
+    }
+
+    /// The next value that will be generated
+    pub fn next_value(&self) -> u64 {
+        self.next
+    }
+
+    /// Restart the sequence
+    pub fn reset(&mut self) {
+        self.next = self.start;
+    }
+
+    pub fn output(&self) -> &Port<C, D> {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut Port<C, D> {
+        &mut self.output
+    }
+
+    /// Activate the output by setting its control
+    pub fn request_output(&mut self, ctrl: C) {
+        self.output.incoming = Some(ctrl);
+    }
+
+    /// Check if the output has been activated
+    pub fn is_output_requested(&self) -> bool {
+        self.output.incoming.is_some()
+    }
+
+    fn advance(&mut self) -> u64 {
+        let value = self.next;
+        self.next = if let Some(modulus) = self.modulus {
+            // Cannot overflow
+            ((u128::from(value) + u128::from(self.step)) % u128::from(modulus)) as u64
+        } else {
+            value.wrapping_add(self.step)
+        };
+        value
+    }
+}
+
+impl<C, D> NodeKind for CounterSource<C, D> {
+    fn kind(&self) -> &'static str {
+        "counter-source"
+    }
+
+    fn params(&self) -> NodeParams {
+        let mut params = NodeParams::new();
+        params.insert("start".to_owned(), NodeParam::UInt(self.start));
+        params.insert("step".to_owned(), NodeParam::UInt(self.step));
+        if let Some(modulus) = self.modulus {
+            params.insert("modulus".to_owned(), NodeParam::UInt(modulus));
+        }
+        params
+    }
+}
+
+impl<C, D> Node<C, D> for CounterSource<C, D>
+where
+    C: 'static,
+    D: From<u64> + 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for CounterSource<C, D> {
+    fn num_inputs(&self) -> usize {
+        0
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        _packet: Packet<D, C>,
+    ) {
+        unimplemented!();
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        unimplemented!();
+    }
+}
+
+impl<C, D> NodeOutputs<C, D> for CounterSource<C, D> {
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.try_dispatch_packet()
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        if output_index != 0 {
+            return false;
+        }
+        f(&mut self.output);
+        true
+    }
+}
+
+impl<C, D> NodeProcessor for CounterSource<C, D>
+where
+    D: From<u64>,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        if self.output.incoming.is_none() {
+            self.output.outgoing = None;
+            return;
+        }
+        self.output.outgoing = Some(D::from(self.advance()));
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        // No inputs, nothing to do
+    }
+}
+
+/// A node that transforms the control payload during the
+/// backward pass
+///
+/// Data values are passed through unmodified during the forward
+/// pass. During the backward pass the control of the output is
+/// mapped into the control of the input.
+///
+/// This enables a pull-based demand model: Sinks request their
+/// inputs with a control payload that describes what they need,
+/// e.g. a resolution or a number of samples. Each node upstream
+/// could transform this request into the requirements for its
+/// own inputs until the demand finally reaches the sources.
+#[allow(missing_debug_implementations)]
+pub struct RequestMapNode<C, D, F> {
+    input: Port<D, C>,
+    output: Port<C, D>,
+    map_request: F,
+}
+
+impl<C, D, F> RequestMapNode<C, D, F>
+where
+    F: Fn(&C) -> C,
+{
+    pub fn new(map_request: F) -> Self {
+        Self {
+            input: Port::new(),
+            output: Port::new(),
+            map_request,
+        }
+    }
+
+    pub fn input(&self) -> &Port<D, C> {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut Port<D, C> {
+        &mut self.input
+    }
+
+    pub fn output(&self) -> &Port<C, D> {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut Port<C, D> {
+        &mut self.output
+    }
+}
+
+impl<C, D, F> Node<C, D> for RequestMapNode<C, D, F>
+where
+    C: 'static,
+    D: 'static,
+    F: Fn(&C) -> C + 'static,
+{
+}
+
+impl<C, D, F> NodeInputs<C, D> for RequestMapNode<C, D, F>
+where
+    F: Fn(&C) -> C,
+{
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.try_dispatch_packet()
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        if input_index != 0 {
+            return false;
+        }
+        f(&mut self.input);
+        true
+    }
+}
+
+impl<C, D, F> NodeOutputs<C, D> for RequestMapNode<C, D, F>
+where
+    F: Fn(&C) -> C,
+{
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.try_dispatch_packet()
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        if output_index != 0 {
+            return false;
+        }
+        f(&mut self.output);
+        true
+    }
+}
+
+impl<C, D, F> NodeProcessor for RequestMapNode<C, D, F>
+where
+    F: Fn(&C) -> C,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        let value = self.input.incoming.take();
+        if self.output.incoming.is_none() {
+            self.output.outgoing = None;
+            return;
+        }
+        self.output.outgoing = value;
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        self.input.outgoing = self.output.incoming.as_ref().map(&self.map_request);
+    }
+}
+
+/// A binary operation of a `CalcNode`
+pub type CalcOperation = Box<dyn Fn(f64, f64) -> f64>;
+
+/// An arithmetic node with two inputs and a configurable
+/// list of outputs
+///
+/// Each output is calculated by applying a binary operation
+/// to the values of both inputs. The value of an output is
+/// only calculated if this output is active and if values for
+/// both inputs are available.
+#[allow(missing_debug_implementations)]
+pub struct CalcNode<C> {
+    inputs: [Port<f64, C>; 2],
+    outputs: VecPortBay<C, f64>,
+    operations: Vec<CalcOperation>,
+}
+
+impl<C> Default for CalcNode<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> CalcNode<C> {
+    pub const fn input_index_lhs() -> PortIndex {
+        PortIndex::new(0)
+    }
+
+    pub const fn input_index_rhs() -> PortIndex {
+        PortIndex::new(1)
+    }
+
+    /// Create a new node without any outputs
+    pub fn new() -> Self {
+        Self::with_operations(Vec::new())
+    }
+
+    /// Create a new node with one output per operation
+    pub fn with_operations(operations: Vec<CalcOperation>) -> Self {
+        Self {
+            inputs: [Port::new(), Port::new()],
+            outputs: VecPortBay::new(operations.len()),
+            operations,
+        }
+    }
+
+    /// Create a new node with the following outputs:
+    ///
+    ///   0. Negation of lhs
+    ///   1. Negation of rhs
+    ///   2. Sum
+    ///   3. Difference
+    ///   4. Product
+    pub fn with_default_operations() -> Self {
+        Self::with_operations(vec![
+            Box::new(|lhs, _| -lhs),
+            Box::new(|_, rhs| -rhs),
+            Box::new(|lhs, rhs| lhs + rhs),
+            Box::new(|lhs, rhs| lhs - rhs),
+            Box::new(|lhs, rhs| lhs * rhs),
+        ])
+    }
+
+    /// Append a new output that is calculated by
+    /// the given operation
+    ///
+    /// Returns the index of the new output.
+    pub fn add_operation(&mut self, operation: impl Fn(f64, f64) -> f64 + 'static) -> PortIndex {
+        self.operations.push(Box::new(operation));
+        // Existing outputs might already be connected and active
+        let output_index = self.outputs.push_port();
+        debug_assert_eq!(self.operations.len(), self.outputs.num_ports());
+        output_index
+    }
+
+    pub fn input(&self, input_index: PortIndex) -> &Port<f64, C> {
+        &self.inputs[usize::from(input_index)]
+    }
+
+    pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<f64, C> {
+        &mut self.inputs[usize::from(input_index)]
+    }
+
+    pub fn output(&self, output_index: PortIndex) -> &Port<C, f64> {
+        self.outputs.port(output_index)
+    }
+
+    pub fn output_mut(&mut self, output_index: PortIndex) -> &mut Port<C, f64> {
+        self.outputs.port_mut(output_index)
+    }
+}
+
+impl<C> Node<C, f64> for CalcNode<C> where C: Clone + JoinablePortControl + 'static {}
+
+impl<C> NodeInputs<C, f64> for CalcNode<C>
+where
+    C: Clone + JoinablePortControl,
+{
+    fn num_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<f64, C>,
+    ) {
+        self.input_mut(input_index).accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, f64>> {
+        self.input_mut(input_index).try_dispatch_packet()
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<f64, C>),
+    ) -> bool {
+        self.inputs.get_mut(input_index.index()).map(f).is_some()
+    }
+}
+
+impl<C> NodeOutputs<C, f64> for CalcNode<C>
+where
+    C: Clone + JoinablePortControl,
+{
+    fn num_outputs(&self) -> usize {
+        self.outputs.num_ports()
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+        packet: Packet<C, f64>,
+    ) {
+        self.outputs.accept_packet(output_index, packet)
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+    ) -> Option<Packet<f64, C>> {
+        self.outputs.try_dispatch_packet(output_index)
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, f64>),
+    ) -> bool {
+        self.outputs.get_port_mut(output_index).map(f).is_some()
+    }
+}
+
+impl<C> NodeProcessor for CalcNode<C>
+where
+    C: Clone + JoinablePortControl,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        let lhs = self.input_mut(Self::input_index_lhs()).incoming.take();
+        let rhs = self.input_mut(Self::input_index_rhs()).incoming.take();
+        for (output_port, operation) in self.outputs.ports_mut().zip(self.operations.iter()) {
+            if output_port.incoming.is_none() {
+                output_port.outgoing = None;
+                continue;
+            }
+            output_port.outgoing = match (lhs, rhs) {
+                (Some(lhs), Some(rhs)) => Some(operation(lhs, rhs)),
+                _ => None,
+            };
+        }
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        // Both inputs are needed for every active output
+        let ctrl = self.outputs.ports().fold(None, |ctrl, port| {
+            if let Some(ctrl) = ctrl {
+                Some(C::join_next_port_control(ctrl, port.incoming.as_ref()))
+            } else {
+                port.incoming.clone()
+            }
+        });
+        for input_port in self.inputs.iter_mut() {
+            input_port.outgoing = ctrl.clone();
+        }
+    }
+}
+
+/// A sink that silently discards the value of its single input
+///
+/// Unlike an unconnected branch the input of this node is always
+/// activated during the backward pass with the given control. All
+/// preceding nodes will continue to calculate their values, which
+/// are then simply dropped. This is useful for temporarily ignoring
+/// the results of a branch while keeping its computational load.
+/// Prefer to disconnect the branch if its computation should be
+/// skipped entirely.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DropNode<C, D> {
+    input: Port<D, C>,
+    ctrl: C,
+}
+
+impl<C, D> DropNode<C, D> {
+    pub fn new(ctrl: C) -> Self {
+        Self {
+            input: Port::new(),
+            ctrl,
+        }
+    }
+
+    pub fn input(&self) -> &Port<D, C> {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut Port<D, C> {
+        &mut self.input
+    }
+}
+
+impl<C, D> NodeKind for DropNode<C, D> {
+    fn kind(&self) -> &'static str {
+        "drop"
+    }
+}
+
+impl<C, D> Node<C, D> for DropNode<C, D>
+where
+    C: Clone + 'static,
+    D: 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for DropNode<C, D>
+where
+    C: Clone,
+{
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.try_dispatch_packet()
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        if input_index != 0 {
+            return false;
+        }
+        f(&mut self.input);
+        true
+    }
+}
+
+impl<C, D> NodeOutputs<C, D> for DropNode<C, D> {
+    fn num_outputs(&self) -> usize {
+        0
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        _packet: Packet<C, D>,
+    ) {
+        unimplemented!();
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        unimplemented!();
+    }
+}
+
+impl<C, D> NodeProcessor for DropNode<C, D>
+where
+    C: Clone,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        self.input.incoming = None;
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        self.input.outgoing = Some(self.ctrl.clone());
+    }
+}
+
+/// A node wrapper that reuses the previous results of the
+/// wrapped node if its inputs didn't change
+///
+/// The wrapped node is only invoked during the forward pass if
+/// either the values of its inputs changed since the last
+/// invocation or if a requested output has not been cached yet.
+/// Otherwise the cached output values are dispatched again.
+///
+/// The wrapped node is supposed to be deterministic, i.e. it
+/// must produce the same outputs for the same inputs.
+#[derive(Debug, Clone)]
+pub struct MemoizeNode<N, C, D> {
+    node: N,
+    inputs: Vec<Option<D>>,
+    last_inputs: Option<Vec<Option<D>>>,
+    outputs: Vec<Option<D>>,
+    output_ctrls: Vec<Option<C>>,
+    reuse_outputs: bool,
+}
+
+impl<N, C, D> MemoizeNode<N, C, D>
+where
+    N: Node<C, D>,
+{
+    pub fn new(node: N) -> Self {
+        let num_inputs = node.num_inputs();
+        let num_outputs = node.num_outputs();
+        Self {
+            node,
+            inputs: std::iter::repeat_with(|| None).take(num_inputs).collect(),
+            last_inputs: None,
+            outputs: std::iter::repeat_with(|| None).take(num_outputs).collect(),
+            output_ctrls: std::iter::repeat_with(|| None).take(num_outputs).collect(),
+            reuse_outputs: false,
+        }
+    }
+
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    pub fn node_mut(&mut self) -> &mut N {
+        &mut self.node
+    }
+
+    pub fn into_inner(self) -> N {
+        self.node
+    }
+
+    /// Discard all cached values
+    ///
+    /// The wrapped node will be invoked on the next forward pass.
+    pub fn reset(&mut self) {
+        self.last_inputs = None;
+        for output in self.outputs.iter_mut() {
+            *output = None;
+        }
+    }
+}
+
+impl<N, C, D> Node<C, D> for MemoizeNode<N, C, D>
+where
+    N: Node<C, D> + 'static,
+    C: Clone + 'static,
+    D: Clone + PartialEq + 'static,
+{
+}
+
+impl<N, C, D> NodeInputs<C, D> for MemoizeNode<N, C, D>
+where
+    N: Node<C, D>,
+    D: Clone,
+{
+    fn num_inputs(&self) -> usize {
+        self.node.num_inputs()
+    }
+
+    fn max_fan_in(&self, input_index: PortIndex) -> Option<usize> {
+        self.node.max_fan_in(input_index)
+    }
+
+    fn resize_inputs(&mut self, token: AccessToken, num_inputs: usize) {
+        self.node.resize_inputs(token, num_inputs);
+        self.inputs.resize_with(num_inputs, || None);
+        self.last_inputs = None;
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        self.inputs[usize::from(input_index)] = Some(packet.payload.clone());
+        self.node.accept_input_packet(token, input_index, packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        self.node.try_dispatch_input_packet(token, input_index)
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.node.with_input_port_mut(input_index, f)
+    }
+}
+
+impl<N, C, D> NodeOutputs<C, D> for MemoizeNode<N, C, D>
+where
+    N: Node<C, D>,
+    C: Clone,
+    D: Clone,
+{
+    fn num_outputs(&self) -> usize {
+        self.node.num_outputs()
+    }
+
+    fn resize_outputs(&mut self, token: AccessToken, num_outputs: usize) {
+        self.node.resize_outputs(token, num_outputs);
+        self.outputs.resize_with(num_outputs, || None);
+        self.output_ctrls.resize_with(num_outputs, || None);
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        token: AccessToken,
+        output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        self.output_ctrls[usize::from(output_index)] = Some(packet.payload.clone());
+        self.node.accept_output_packet(token, output_index, packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        token: AccessToken,
+        output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        let index = usize::from(output_index);
+        if self.reuse_outputs {
+            let payload = self.outputs[index].clone()?;
+            let ctrl = self.output_ctrls[index].take()?;
+            Some(Packet {
+                payload,
+                piggyback: Some(ctrl),
+            })
+        } else {
+            let packet = self.node.try_dispatch_output_packet(token, output_index);
+            if let Some(packet) = &packet {
+                self.outputs[index] = Some(packet.payload.clone());
+            }
+            packet
+        }
+    }
+
+    fn reset_output_control(&mut self, token: AccessToken, output_index: PortIndex) {
+        self.output_ctrls[usize::from(output_index)] = None;
+        self.node.reset_output_control(token, output_index);
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        self.node.with_output_port_mut(output_index, f)
+    }
+}
+
+impl<N, C, D> NodeProcessor for MemoizeNode<N, C, D>
+where
+    N: Node<C, D>,
+    D: Clone + PartialEq,
+{
+    fn process_inputs(&mut self, token: AccessToken) {
+        let inputs: Vec<_> = self.inputs.iter_mut().map(Option::take).collect();
+        let unchanged_inputs = self.last_inputs.as_ref() == Some(&inputs);
+        let cached_outputs = self
+            .output_ctrls
+            .iter()
+            .zip(self.outputs.iter())
+            .all(|(ctrl, output)| ctrl.is_none() || output.is_some());
+        self.reuse_outputs = unchanged_inputs && cached_outputs;
+        if !self.reuse_outputs {
+            for (ctrl, output) in self.output_ctrls.iter_mut().zip(self.outputs.iter_mut()) {
+                *ctrl = None;
+                *output = None;
+            }
+            self.node.process_inputs(token);
+        }
+        self.last_inputs = Some(inputs);
+    }
+
+    fn process_outputs(&mut self, token: AccessToken) {
+        self.node.process_outputs(token);
+    }
+
+    fn on_input_connected(&mut self, token: AccessToken, input_index: PortIndex) {
+        self.node.on_input_connected(token, input_index);
+    }
+
+    fn on_input_disconnected(&mut self, token: AccessToken, input_index: PortIndex) {
+        self.node.on_input_disconnected(token, input_index);
+    }
+
+    fn on_output_connected(&mut self, token: AccessToken, output_index: PortIndex) {
+        self.node.on_output_connected(token, output_index);
+    }
+
+    fn on_output_disconnected(&mut self, token: AccessToken, output_index: PortIndex) {
+        self.node.on_output_disconnected(token, output_index);
+    }
+}
+
+/// A node that tags values with a sequence number
+///
+/// Values are passed through as pairs `(u64, T)` and the first
+/// component is replaced by a sequence number. The sequence number
+/// is incremented on each forward pass, regardless of whether a
+/// value has been received or not. This allows downstream nodes to
+/// verify the ordering of values and to detect dropped passes.
+///
+/// Since all ports within a flow graph share the same data type,
+/// upstream nodes are supposed to provide pairs with an arbitrary
+/// sequence number that is then overwritten by this node.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SequenceTagNode<C, T> {
+    input: Port<(u64, T), C>,
+    output: Port<C, (u64, T)>,
+    sequence_number: u64,
+}
+
+impl<C, T> Default for SequenceTagNode<C, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C, T> SequenceTagNode<C, T> {
+    pub fn new() -> Self {
+        Self {
+            input: Port::new(),
+            output: Port::new(),
+            sequence_number: 0,
+        }
+    }
+
+    /// The sequence number for the next forward pass
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    /// Restart the sequence at 0
+    pub fn reset(&mut self) {
+        self.sequence_number = 0;
+    }
+
+    pub fn input(&self) -> &Port<(u64, T), C> {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut Port<(u64, T), C> {
+        &mut self.input
+    }
+
+    pub fn output(&self) -> &Port<C, (u64, T)> {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut Port<C, (u64, T)> {
+        &mut self.output
+    }
+}
+
+impl<C, T> NodeKind for SequenceTagNode<C, T> {
+    fn kind(&self) -> &'static str {
+        "sequence-tag"
+    }
+}
+
+impl<C, T> Node<C, (u64, T)> for SequenceTagNode<C, T>
+where
+    C: Clone + 'static,
+    T: 'static,
+{
+}
+
+impl<C, T> NodeInputs<C, (u64, T)> for SequenceTagNode<C, T> {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        packet: Packet<(u64, T), C>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<C, (u64, T)>> {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.try_dispatch_packet()
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<(u64, T), C>),
+    ) -> bool {
+        if input_index != 0 {
+            return false;
+        }
+        f(&mut self.input);
+        true
+    }
+}
+
+impl<C, T> NodeOutputs<C, (u64, T)> for SequenceTagNode<C, T> {
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<C, (u64, T)>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<(u64, T), C>> {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.try_dispatch_packet()
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, (u64, T)>),
+    ) -> bool {
+        if output_index != 0 {
+            return false;
+        }
+        f(&mut self.output);
+        true
+    }
+}
+
+impl<C, T> NodeProcessor for SequenceTagNode<C, T>
+where
+    C: Clone,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        let sequence_number = self.sequence_number;
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        let value = self.input.incoming.take();
+        if self.output.incoming.is_none() {
+            self.output.outgoing = None;
+            return;
+        }
+        self.output.outgoing = value.map(|(_, value)| (sequence_number, value));
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        self.input.outgoing = self.output.incoming.clone();
+    }
+}
+
+/// A node that emits the difference between consecutive values
+///
+/// The previous value is cached and subtracted from the current
+/// value. No value is emitted for the first value after creating
+/// or resetting the node. Passes without an input value emit
+/// nothing and retain the previous value.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DiffNode<C, D> {
+    input: Port<D, C>,
+    output: Port<C, D>,
+    previous: Option<D>,
+}
+
+impl<C, D> Default for DiffNode<C, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C, D> DiffNode<C, D> {
+    pub fn new() -> Self {
+        Self {
+            input: Port::new(),
+            output: Port::new(),
+            previous: None,
+        }
+    }
+
+    /// Discard the previous value
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    pub fn input(&self) -> &Port<D, C> {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut Port<D, C> {
+        &mut self.input
+    }
+
+    pub fn output(&self) -> &Port<C, D> {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut Port<C, D> {
+        &mut self.output
+    }
+}
+
+impl<C, D> Node<C, D> for DiffNode<C, D>
+where
+    C: Clone + 'static,
+    D: Sub<Output = D> + Clone + 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for DiffNode<C, D> {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.try_dispatch_packet()
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        if input_index != 0 {
+            return false;
+        }
+        f(&mut self.input);
+        true
+    }
+}
+
+impl<C, D> NodeOutputs<C, D> for DiffNode<C, D> {
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.try_dispatch_packet()
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        if output_index != 0 {
+            return false;
+        }
+        f(&mut self.output);
+        true
+    }
+}
+
+impl<C, D> NodeProcessor for DiffNode<C, D>
+where
+    C: Clone,
+    D: Sub<Output = D> + Clone,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        let previous = &mut self.previous;
+        let diff = self.input.incoming.take().and_then(|current| {
+            previous
+                .replace(current.clone())
+                .map(|previous| current - previous)
+        });
+        if self.output.incoming.is_none() {
+            self.output.outgoing = None;
+            return;
+        }
+        self.output.outgoing = diff;
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        self.input.outgoing = self.output.incoming.clone();
+    }
+}
+
+/// A node that sums up the values of all inputs
+///
+/// The single output emits the sum of all inputs that received
+/// a value, summed up in order of the input ports. No value is
+/// emitted if none of the inputs received a value.
+///
+/// All inputs are requested with the control of the output.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AdderNode<C, D> {
+    inputs: VecPortBay<D, C>,
+    output: Port<C, D>,
+}
+
+impl<C, D> AdderNode<C, D> {
+    pub fn new(num_inputs: usize) -> Self {
+        Self {
+            inputs: VecPortBay::new(num_inputs),
+            output: Port::new(),
+        }
+    }
+
+    pub fn input(&self, input_index: PortIndex) -> &Port<D, C> {
+        self.inputs.port(input_index)
+    }
+
+    pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<D, C> {
+        self.inputs.port_mut(input_index)
+    }
+
+    pub fn output(&self) -> &Port<C, D> {
+        &self.output
+    }
+
+    pub fn output_mut(&mut self) -> &mut Port<C, D> {
+        &mut self.output
+    }
+}
+
+impl<C, D> Node<C, D> for AdderNode<C, D>
+where
+    C: Clone + 'static,
+    D: Add<Output = D> + 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for AdderNode<C, D> {
+    fn num_inputs(&self) -> usize {
+        self.inputs.num_ports()
+    }
+
+    fn resize_inputs(&mut self, _token: AccessToken, num_inputs: usize) {
+        self.inputs.resize_ports(num_inputs);
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        self.inputs.accept_packet(input_index, packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        self.inputs.try_dispatch_packet(input_index)
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.inputs.get_port_mut(input_index).map(f).is_some()
+    }
+}
+
+impl<C, D> NodeOutputs<C, D> for AdderNode<C, D> {
+    fn num_outputs(&self) -> usize {
+        1
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.accept_packet(packet);
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        _output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        debug_assert_eq!(PortIndex::new(0), _output_index);
+        self.output.try_dispatch_packet()
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        if output_index != 0 {
+            return false;
+        }
+        f(&mut self.output);
+        true
+    }
+}
+
+impl<C, D> NodeProcessor for AdderNode<C, D>
+where
+    C: Clone,
+    D: Add<Output = D>,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        let sum = self
+            .inputs
+            .ports_mut()
+            .filter_map(|port| port.incoming.take())
+            .fold(None, |sum, value| {
+                if let Some(sum) = sum {
+                    Some(sum + value)
+                } else {
+                    Some(value)
+                }
+            });
+        if self.output.incoming.is_none() {
+            self.output.outgoing = None;
+            return;
+        }
+        self.output.outgoing = sum;
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        for port in self.inputs.ports_mut() {
+            port.outgoing = self.output.incoming.clone();
+        }
+    }
+}
+
+/// A node that routes values to outputs depending on a key
+///
+/// The key is computed from each input value with a closure. The
+/// value is then passed to the output that has been registered for
+/// this key. Values with unregistered keys are passed to the default
+/// output if configured or dropped otherwise. A value is also dropped
+/// if the corresponding output is not active.
+///
+/// The input is requested by joining the controls of all outputs.
+#[allow(missing_debug_implementations)]
+pub struct KeyRouterNode<C, D, K, F> {
+    input: Port<D, C>,
+    outputs: VecPortBay<C, D>,
+    routes: HashMap<K, PortIndex>,
+    default_output: Option<PortIndex>,
+    route_key: F,
+}
+
+impl<C, D, K, F> KeyRouterNode<C, D, K, F>
+where
+    K: Hash + Eq,
+    F: Fn(&D) -> K,
+{
+    pub fn new(num_outputs: usize, route_key: F) -> Self {
+        Self {
+            input: Port::new(),
+            outputs: VecPortBay::new(num_outputs),
+            routes: HashMap::new(),
+            default_output: None,
+            route_key,
+        }
+    }
+
+    /// Route all values with the given key to an output
+    ///
+    /// Returns the output that has previously been registered
+    /// for this key.
+    pub fn set_route(&mut self, key: K, output_index: PortIndex) -> Option<PortIndex> {
+        debug_assert!(output_index < self.outputs.num_ports());
+        self.routes.insert(key, output_index)
+    }
+
+    pub fn remove_route(&mut self, key: &K) -> Option<PortIndex> {
+        self.routes.remove(key)
+    }
+
+    pub fn default_output(&self) -> Option<PortIndex> {
+        self.default_output
+    }
+
+    /// Route all values with unregistered keys to an output
+    ///
+    /// Those values are dropped if no default output is configured.
+    pub fn set_default_output(&mut self, default_output: Option<PortIndex>) {
+        if let Some(output_index) = default_output {
+            debug_assert!(output_index < self.outputs.num_ports());
+        }
+        self.default_output = default_output;
+    }
+
+    pub fn input(&self) -> &Port<D, C> {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut Port<D, C> {
+        &mut self.input
+    }
+
+    pub fn output(&self, output_index: PortIndex) -> &Port<C, D> {
+        self.outputs.port(output_index)
+    }
+
+    pub fn output_mut(&mut self, output_index: PortIndex) -> &mut Port<C, D> {
+        self.outputs.port_mut(output_index)
+    }
+}
+
+impl<C, D, K, F> Node<C, D> for KeyRouterNode<C, D, K, F>
+where
+    C: Clone + JoinablePortControl + 'static,
+    D: 'static,
+    K: Hash + Eq + 'static,
+    F: Fn(&D) -> K + 'static,
+{
+}
+
+impl<C, D, K, F> NodeInputs<C, D> for KeyRouterNode<C, D, K, F> {
+    fn num_inputs(&self) -> usize {
+        1
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        _input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        debug_assert_eq!(PortIndex::new(0), _input_index);
+        self.input.try_dispatch_packet()
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        if input_index != 0 {
+            return false;
+        }
+        f(&mut self.input);
+        true
+    }
+}
+
+impl<C, D, K, F> NodeOutputs<C, D> for KeyRouterNode<C, D, K, F> {
+    fn num_outputs(&self) -> usize {
+        self.outputs.num_ports()
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        self.outputs.accept_packet(output_index, packet)
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        self.outputs.try_dispatch_packet(output_index)
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        self.outputs.get_port_mut(output_index).map(f).is_some()
+    }
+}
+
+impl<C, D, K, F> NodeProcessor for KeyRouterNode<C, D, K, F>
+where
+    C: Clone + JoinablePortControl,
+    K: Hash + Eq,
+    F: Fn(&D) -> K,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        for output_port in self.outputs.ports_mut() {
+            output_port.outgoing = None;
+        }
+        if let Some(value) = self.input.incoming.take() {
+            let key = (self.route_key)(&value);
+            let output_index = self.routes.get(&key).copied().or(self.default_output);
+            if let Some(output_index) = output_index {
+                let output_port = self.outputs.port_mut(output_index);
+                if output_port.incoming.is_some() {
+                    output_port.outgoing = Some(value);
+                }
+            }
+        }
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        self.input.outgoing = self.outputs.ports().fold(None, |ctrl, port| {
+            if let Some(ctrl) = ctrl {
+                Some(ctrl.join_next_port_control(port.incoming.as_ref()))
+            } else {
+                port.incoming.clone()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{flow::Flow, testing::fixtures::*};
+
+    use std::cell::Cell;
+
+    #[test]
+    fn calc_node_add_operation() {
+        let mut node = CalcNode::<()>::with_default_operations();
+        let num_outputs = node.num_outputs();
+        let div_index = node.add_operation(|lhs, rhs| lhs / rhs);
+        assert_eq!(PortIndex::new(num_outputs), div_index);
+        assert_eq!(num_outputs + 1, node.num_outputs());
+        let mut flow: Flow<CalcNode<()>, (), f64> = Flow::new();
+        let node_id = flow.add_node(node);
+        let node = flow.node_mut(node_id);
+        node.output_mut(PortIndex::new(2)).incoming = Some(());
+        node.output_mut(div_index).incoming = Some(());
+        flow.process_outputs(node_id);
+        let node = flow.node_mut(node_id);
+        assert!(node.input(PortIndex::new(0)).outgoing.is_some());
+        node.input_mut(PortIndex::new(0)).incoming = Some(6.0);
+        node.input_mut(PortIndex::new(1)).incoming = Some(3.0);
+        flow.process_inputs(node_id);
+        let node = flow.node(node_id);
+        assert_eq!(Some(9.0), node.output(PortIndex::new(2)).outgoing);
+        assert_eq!(Some(2.0), node.output(div_index).outgoing);
+        assert_eq!(None, node.output(PortIndex::new(4)).outgoing);
+    }
+
+    #[test]
+    fn calc_node_add_operation_preserves_existing_outputs() {
+        let mut node = CalcNode::<()>::with_default_operations();
+        node.output_mut(PortIndex::new(0)).incoming = Some(());
+        node.output_mut(PortIndex::new(1)).outgoing = Some(1.0);
+        node.add_operation(|lhs, rhs| lhs / rhs);
+        assert_eq!(Some(()), node.output(PortIndex::new(0)).incoming);
+        assert_eq!(Some(1.0), node.output(PortIndex::new(1)).outgoing);
+    }
+
+    #[test]
+    fn many_to_one_joiner_joins_active_inputs() {
+        let mut flow: Flow<ManyToOneJoiner<(), String>, (), String> = Flow::new();
+        let joiner_id = flow.add_node(ManyToOneJoiner::new(3));
+        flow.process_outputs(joiner_id);
+        let joiner = flow.node_mut(joiner_id);
+        assert!(!joiner.is_input_requested(PortIndex::new(0)));
+        joiner.request_output(());
+        flow.process_outputs(joiner_id);
+        let joiner = flow.node_mut(joiner_id);
+        assert!((0..3).all(|index| joiner.is_input_requested(PortIndex::new(index))));
+        // The missing value of the inactive input is skipped
+        joiner.input_mut(PortIndex::new(0)).incoming = Some("a".to_owned());
+        joiner.input_mut(PortIndex::new(2)).incoming = Some("c".to_owned());
+        flow.process_inputs(joiner_id);
+        let joiner = flow.node_mut(joiner_id);
+        assert_eq!(Some("ac".to_owned()), joiner.output().outgoing);
+        assert!(joiner.input(PortIndex::new(0)).incoming.is_none());
+        assert!(joiner.input(PortIndex::new(2)).incoming.is_none());
+    }
+
+    #[test]
+    fn many_to_one_joiner_consumes_values_of_inactive_output() {
+        let mut flow: Flow<ManyToOneJoiner<(), Vec<i32>>, (), Vec<i32>> = Flow::new();
+        let joiner_id = flow.add_node(ManyToOneJoiner::new(2));
+        let joiner = flow.node_mut(joiner_id);
+        joiner.input_mut(PortIndex::new(0)).incoming = Some(vec![1]);
+        joiner.input_mut(PortIndex::new(1)).incoming = Some(vec![2, 3]);
+        flow.process_inputs(joiner_id);
+        let joiner = flow.node_mut(joiner_id);
+        assert_eq!(None, joiner.output().outgoing);
+        assert!(joiner.input(PortIndex::new(1)).incoming.is_none());
+        joiner.request_output(());
+        joiner.input_mut(PortIndex::new(1)).incoming = Some(vec![4]);
+        joiner.input_mut(PortIndex::new(0)).incoming = Some(vec![5, 6]);
+        flow.process_inputs(joiner_id);
+        // Joined in order of the inputs
+        assert_eq!(Some(vec![5, 6, 4]), flow.node(joiner_id).output().outgoing);
+    }
+
+    #[test]
+    fn debug_printer_sink_print_interval() {
+        let mut sink = DebugPrinterSink::<(), i32>::new(1);
+        assert_eq!(1, sink.print_interval());
+        assert!((0..3).all(|_| sink.count_pass()));
+        sink.set_print_interval(3);
+        let printed: Vec<_> = (0..7).map(|_| sink.count_pass()).collect();
+        assert_eq!(vec![true, false, false, true, false, false, true], printed);
+        // Restarts with the first pass
+        sink.set_print_interval(2);
+        assert!(sink.count_pass());
+        assert!(!sink.count_pass());
+        sink.set_print_interval(0);
+        assert!((0..3).all(|_| !sink.count_pass()));
+    }
+
+    #[test]
+    fn debug_printer_sink_consumes_unprinted_values() {
+        let mut sink = DebugPrinterSink::<(), i32>::new(1);
+        sink.set_print_interval(0);
+        let mut flow: Flow<DebugPrinterSink<(), i32>, (), i32> = Flow::new();
+        let sink_id = flow.add_node(sink);
+        for value in 0..3 {
+            flow.node_mut(sink_id).input_mut(PortIndex::new(0)).incoming = Some(value);
+            flow.process_inputs(sink_id);
+            assert!(flow
+                .node(sink_id)
+                .input(PortIndex::new(0))
+                .incoming
+                .is_none());
+        }
+    }
+
+    crate::node_enum! {
+        enum MemoizeTestNode: Node<(), i32> {
+            Source(FnSource<(), i32, BoxedGenerate>),
+            Memoize(MemoizeNode<FnNode<(), i32, BoxedCompute>, (), i32>),
+            Sink(RingBufferSink<(), i32>),
+        }
+    }
+
+    #[test]
+    fn source_fn_generates_values_while_requested() {
+        let mut counter = 0;
+        let generate: BoxedGenerate = Box::new(move || {
+            counter += 1;
+            counter
+        });
+        let mut flow: AdderFlow = Flow::new();
+        let source_id = flow.add_node(source_fn(generate).into());
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(1, 8).into());
+        flow.connect(socket(source_id, 0), socket(sink_id, 0));
+        // Nothing is generated while the sink is not requested
+        flow.execute().unwrap();
+        for _ in 0..3 {
+            unwrap_node!(flow.node_mut(sink_id), AdderTestNode::Sink).request_all_inputs(());
+            flow.execute().unwrap();
+        }
+        let sink = unwrap_node!(flow.node(sink_id), AdderTestNode::Sink);
+        assert_eq!(vec![1, 2, 3], recent_values(sink, 0));
+    }
+
+    #[test]
+    fn fn_node_adds_two_inputs() {
+        let (mut flow, [_, _, _, sink_id], invocations) =
+            adder_flow(counter_source(1, 1), counter_source(10, 10));
+        // Nothing is computed while the sum is not requested
+        flow.execute().unwrap();
+        assert_eq!(0, invocations.get());
+        for _ in 0..3 {
+            unwrap_node!(flow.node_mut(sink_id), AdderTestNode::Sink).request_all_inputs(());
+            flow.execute().unwrap();
+        }
+        assert_eq!(3, invocations.get());
+        let sink = unwrap_node!(flow.node(sink_id), AdderTestNode::Sink);
+        assert_eq!(vec![11, 22, 33], recent_values(sink, 0));
+    }
+
+    #[test]
+    fn fn_node_discards_values_of_inactive_outputs() {
+        let copy: BoxedCompute = Box::new(|inputs, outputs| {
+            for output in outputs.iter_mut() {
+                *output = inputs[0];
+            }
+        });
+        let mut flow: Flow<FnNode<(), i32, BoxedCompute>, (), i32> = Flow::new();
+        let node_id = flow.add_node(FnNode::new(1, 2, copy));
+        flow.node_mut(node_id).request_output(PortIndex::new(1), ());
+        flow.process_outputs(node_id);
+        let node = flow.node_mut(node_id);
+        assert!(node.input(PortIndex::new(0)).outgoing.is_some());
+        node.input_mut(PortIndex::new(0)).incoming = Some(7);
+        flow.process_inputs(node_id);
+        let node = flow.node(node_id);
+        assert_eq!(None, node.output(PortIndex::new(0)).outgoing);
+        assert_eq!(Some(7), node.output(PortIndex::new(1)).outgoing);
+        // The input value has been consumed
+        assert_eq!(None, node.input(PortIndex::new(0)).incoming);
+    }
+
+    #[test]
+    fn memoize_node_invokes_node_only_for_changed_inputs() {
+        let invocations = Rc::new(Cell::new(0));
+        let mut values = vec![1, 1, 2, 2, 1, 1].into_iter();
+        let source: BoxedGenerate = Box::new(move || values.next().unwrap());
+        let compute_invocations = Rc::clone(&invocations);
+        let compute: BoxedCompute = Box::new(move |inputs, outputs| {
+            compute_invocations.set(compute_invocations.get() + 1);
+            outputs[0] = inputs[0].map(|value| value * 2);
+        });
+        let mut flow: Flow<MemoizeTestNode, (), i32> = Flow::new();
+        let source_id = flow.add_node(FnSource::new(source).into());
+        let memoize_id = flow.add_node(MemoizeNode::new(FnNode::new(1, 1, compute)).into());
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(1, 8).into());
+        flow.connect(socket(source_id, 0), socket(memoize_id, 0));
+        flow.connect(socket(memoize_id, 0), socket(sink_id, 0));
+        let execute = |flow: &mut Flow<MemoizeTestNode, (), i32>| {
+            unwrap_node!(flow.node_mut(sink_id), MemoizeTestNode::Sink).request_all_inputs(());
+            flow.execute().unwrap();
+        };
+        for _ in 0..4 {
+            execute(&mut flow);
+        }
+        assert_eq!(2, invocations.get());
+        execute(&mut flow);
+        assert_eq!(3, invocations.get());
+        // Discarding the cached values enforces an invocation
+        unwrap_node!(flow.node_mut(memoize_id), MemoizeTestNode::Memoize).reset();
+        execute(&mut flow);
+        assert_eq!(4, invocations.get());
+        let sink = unwrap_node!(flow.node(sink_id), MemoizeTestNode::Sink);
+        assert_eq!(vec![2, 2, 4, 4, 2, 2], recent_values(sink, 0));
+    }
+
+    #[test]
+    fn ring_buffer_sink_discards_oldest_values_when_full() {
+        let mut flow: Flow<RingBufferSink<(), i32>, (), i32> = Flow::new();
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(2, 3));
+        for value in 0..5 {
+            let sink = flow.node_mut(sink_id);
+            sink.input_mut(PortIndex::new(0)).incoming = Some(value);
+            if value % 2 == 0 {
+                sink.input_mut(PortIndex::new(1)).incoming = Some(-value);
+            }
+            flow.process_inputs(sink_id);
+        }
+        let sink = flow.node(sink_id);
+        assert_eq!(3, sink.capacity());
+        assert_eq!(vec![2, 3, 4], recent_values(sink, 0));
+        assert_eq!(vec![0, -2, -4], recent_values(sink, 1));
+        flow.node_mut(sink_id).clear();
+        assert!(recent_values(flow.node(sink_id), 0).is_empty());
+    }
+
+    #[test]
+    fn counter_source_wraps_around_at_modulus() {
+        let mut flow: Flow<CounterSource<(), u64>, (), u64> = Flow::new();
+        let counter_id = flow.add_node(CounterSource::new(7, 3, Some(5)));
+        // The start value is reduced by the modulus
+        assert_eq!(2, flow.node(counter_id).next_value());
+        let mut generate = || {
+            flow.node_mut(counter_id).request_output(());
+            flow.process_inputs(counter_id);
+            flow.node(counter_id).output().outgoing.unwrap()
+        };
+        let values: Vec<_> = std::iter::repeat_with(&mut generate).take(6).collect();
+        assert_eq!(vec![2, 0, 3, 1, 4, 2], values);
+        flow.node_mut(counter_id).reset();
+        assert_eq!(2, flow.node(counter_id).next_value());
+    }
+
+    #[test]
+    fn counter_source_wraps_around_without_modulus() {
+        let mut counter = CounterSource::<(), u64>::new(u64::MAX - 1, 2, None);
+        counter.request_output(());
+        let mut flow: Flow<CounterSource<(), u64>, (), u64> = Flow::new();
+        let counter_id = flow.add_node(counter);
+        flow.process_inputs(counter_id);
+        assert_eq!(Some(u64::MAX - 1), flow.node(counter_id).output().outgoing);
+        assert_eq!(0, flow.node(counter_id).next_value());
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus must not be 0")]
+    fn counter_source_rejects_zero_modulus() {
+        let _ = CounterSource::<(), u64>::new(0, 1, Some(0));
+    }
+
+    #[test]
+    fn sequence_tag_node_tags_values_with_consecutive_numbers() {
+        type TagFlow = Flow<SequenceTagNode<(), char>, (), (u64, char)>;
+        let mut flow: TagFlow = Flow::new();
+        let tag_id = flow.add_node(SequenceTagNode::new());
+        let tag = |flow: &mut TagFlow, value: Option<char>| {
+            let node = flow.node_mut(tag_id);
+            node.output_mut().incoming = Some(());
+            node.input_mut().incoming = value.map(|value| (u64::MAX, value));
+            flow.process_inputs(tag_id);
+            flow.node(tag_id).output().outgoing
+        };
+        assert_eq!(Some((0, 'a')), tag(&mut flow, Some('a')));
+        assert_eq!(Some((1, 'b')), tag(&mut flow, Some('b')));
+        // A dropped pass leaves a gap in the sequence
+        assert_eq!(None, tag(&mut flow, None));
+        assert_eq!(Some((3, 'c')), tag(&mut flow, Some('c')));
+        assert_eq!(4, flow.node(tag_id).sequence_number());
+        flow.node_mut(tag_id).reset();
+        assert_eq!(Some((0, 'd')), tag(&mut flow, Some('d')));
+    }
+
+    fn answer() -> i32 {
+        42
+    }
+
+    fn double_demand(samples: &usize) -> usize {
+        samples * 2
+    }
+
+    crate::node_enum! {
+        enum DemandTestNode: Node<usize, i32> {
+            Source(FnSource<usize, i32, fn() -> i32>),
+            RequestMap(RequestMapNode<usize, i32, fn(&usize) -> usize>),
+            Sink(RingBufferSink<usize, i32>),
+        }
+    }
+
+    #[test]
+    fn request_map_node_maps_control_of_output() {
+        let mut flow: Flow<DemandTestNode, usize, i32> = Flow::new();
+        let source_id = flow.add_node(FnSource::new(answer as fn() -> i32).into());
+        let map_id =
+            flow.add_node(RequestMapNode::new(double_demand as fn(&usize) -> usize).into());
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(1, 4).into());
+        flow.connect(socket(source_id, 0), socket(map_id, 0));
+        flow.connect(socket(map_id, 0), socket(sink_id, 0));
+        unwrap_node!(flow.node_mut(sink_id), DemandTestNode::Sink)
+            .request_input(PortIndex::new(0), 3);
+        flow.process_backward_all().unwrap();
+        // The doubled demand reaches the source
+        let source = unwrap_node!(flow.node(source_id), DemandTestNode::Source);
+        assert_eq!(Some(6), source.output().incoming);
+        flow.process_forward_all().unwrap();
+        // Values are passed through unmodified
+        let sink = unwrap_node!(flow.node(sink_id), DemandTestNode::Sink);
+        assert_eq!(vec![42], recent_values(sink, 0));
+    }
+
+    #[test]
+    fn one_to_many_splitter_ignores_disabled_outputs() {
+        let mut flow: Flow<OneToManySplitter<(), i32>, (), i32> = Flow::new();
+        let splitter_id = flow.add_node(OneToManySplitter::new(2));
+        let splitter = flow.node_mut(splitter_id);
+        assert!(splitter.is_output_enabled(PortIndex::new(1)));
+        splitter.set_output_enabled(PortIndex::new(1), false);
+        splitter.request_output(PortIndex::new(0), ());
+        splitter.request_output(PortIndex::new(1), ());
+        flow.process_outputs(splitter_id);
+        let splitter = flow.node_mut(splitter_id);
+        assert!(splitter.is_input_requested());
+        splitter.input_mut().incoming = Some(5);
+        flow.process_inputs(splitter_id);
+        let splitter = flow.node_mut(splitter_id);
+        assert_eq!(Some(5), splitter.output(PortIndex::new(0)).outgoing);
+        assert_eq!(None, splitter.output(PortIndex::new(1)).outgoing);
+        // Only the disabled output is activated
+        splitter.output_mut(PortIndex::new(0)).incoming = None;
+        flow.process_outputs(splitter_id);
+        let splitter = flow.node_mut(splitter_id);
+        assert!(splitter.is_output_requested(PortIndex::new(1)));
+        assert!(!splitter.is_input_requested());
+        // Enabling the output again restores the request
+        splitter.set_output_enabled(PortIndex::new(1), true);
+        flow.process_outputs(splitter_id);
+        assert!(flow.node(splitter_id).is_input_requested());
+    }
+
+    #[test]
+    fn diff_node_emits_differences_of_consecutive_values() {
+        let mut flow: Flow<DiffNode<(), i32>, (), i32> = Flow::new();
+        let diff_id = flow.add_node(DiffNode::new());
+        let diff = |flow: &mut Flow<DiffNode<(), i32>, (), i32>, value| {
+            let node = flow.node_mut(diff_id);
+            node.output_mut().incoming = Some(());
+            node.input_mut().incoming = value;
+            flow.process_inputs(diff_id);
+            flow.node(diff_id).output().outgoing
+        };
+        let diffs: Vec<_> = [1, 3, 6, 10]
+            .iter()
+            .map(|value| diff(&mut flow, Some(*value)))
+            .collect();
+        assert_eq!(vec![None, Some(2), Some(3), Some(4)], diffs);
+        // The previous value is retained while idle
+        assert_eq!(None, diff(&mut flow, None));
+        assert_eq!(Some(5), diff(&mut flow, Some(15)));
+        flow.node_mut(diff_id).reset();
+        assert_eq!(None, diff(&mut flow, Some(20)));
+        assert_eq!(Some(-5), diff(&mut flow, Some(15)));
+    }
+
+    fn is_even(value: &i32) -> bool {
+        value % 2 == 0
+    }
+
+    type EvenOddRouter = KeyRouterNode<(), i32, bool, fn(&i32) -> bool>;
+
+    fn even_odd_router(num_outputs: usize) -> EvenOddRouter {
+        KeyRouterNode::new(num_outputs, is_even as fn(&i32) -> bool)
+    }
+
+    crate::node_enum! {
+        enum RouterTestNode: Node<(), i32> {
+            Router(EvenOddRouter),
+            Sink(RingBufferSink<(), i32>),
+        }
+    }
+
+    #[test]
+    fn key_router_node_routes_even_and_odd_values() {
+        let mut router = even_odd_router(2);
+        router.set_route(true, PortIndex::new(0));
+        router.set_route(false, PortIndex::new(1));
+        let mut flow: Flow<RouterTestNode, (), i32> = Flow::new();
+        let router_id = flow.add_node(router.into());
+        let even_id = flow.add_node(RingBufferSink::with_capacity(1, 8).into());
+        let odd_id = flow.add_node(RingBufferSink::with_capacity(1, 8).into());
+        flow.connect(socket(router_id, 0), socket(even_id, 0));
+        flow.connect(socket(router_id, 1), socket(odd_id, 0));
+        for value in 0..6 {
+            for sink_id in &[even_id, odd_id] {
+                unwrap_node!(flow.node_mut(*sink_id), RouterTestNode::Sink).request_all_inputs(());
+            }
+            unwrap_node!(flow.node_mut(router_id), RouterTestNode::Router)
+                .input_mut()
+                .incoming = Some(value);
+            flow.execute().unwrap();
+        }
+        let recent =
+            |sink_id| recent_values(unwrap_node!(flow.node(sink_id), RouterTestNode::Sink), 0);
+        assert_eq!(vec![0, 2, 4], recent(even_id));
+        assert_eq!(vec![1, 3, 5], recent(odd_id));
+    }
+
+    #[test]
+    fn key_router_node_routes_unknown_keys_to_default_output() {
+        let mut router = even_odd_router(2);
+        router.set_route(true, PortIndex::new(0));
+        let mut flow: Flow<EvenOddRouter, (), i32> = Flow::new();
+        let router_id = flow.add_node(router);
+        let route = |flow: &mut Flow<EvenOddRouter, (), i32>, value| {
+            let router = flow.node_mut(router_id);
+            router.output_mut(PortIndex::new(0)).incoming = Some(());
+            router.output_mut(PortIndex::new(1)).incoming = Some(());
+            router.input_mut().incoming = Some(value);
+            flow.process_inputs(router_id);
+            let router = flow.node(router_id);
+            (
+                router.output(PortIndex::new(0)).outgoing,
+                router.output(PortIndex::new(1)).outgoing,
+            )
+        };
+        assert_eq!((Some(2), None), route(&mut flow, 2));
+        // Dropped without a default output
+        assert_eq!((None, None), route(&mut flow, 3));
+        flow.node_mut(router_id)
+            .set_default_output(Some(PortIndex::new(1)));
+        assert_eq!((None, Some(3)), route(&mut flow, 3));
+        assert_eq!(
+            Some(PortIndex::new(0)),
+            flow.node_mut(router_id).remove_route(&true)
+        );
+        assert_eq!((None, Some(4)), route(&mut flow, 4));
+    }
+}
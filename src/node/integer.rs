@@ -0,0 +1,417 @@
+use super::{JoinablePortControl, Node, NodeInputs, NodeOutputs, NodeProcessor};
+
+use crate::{
+    flow::AccessToken,
+    port::{Packet, Port, PortBay, PortIndex, VecPortBay},
+};
+
+use num_traits::{
+    CheckedAdd, CheckedMul, CheckedSub, SaturatingAdd, SaturatingMul, SaturatingSub, WrappingAdd,
+    WrappingMul, WrappingSub,
+};
+
+/// Integer types with overflow-aware arithmetic operations
+pub trait OverflowArithmetic:
+    CheckedAdd
+    + CheckedSub
+    + CheckedMul
+    + WrappingAdd
+    + WrappingSub
+    + WrappingMul
+    + SaturatingAdd
+    + SaturatingSub
+    + SaturatingMul
+{
+}
+
+impl<T> OverflowArithmetic for T where
+    T: CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + WrappingAdd
+        + WrappingSub
+        + WrappingMul
+        + SaturatingAdd
+        + SaturatingSub
+        + SaturatingMul
+{
+}
+
+/// Handling of arithmetic overflows
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// Wrap around at the boundary of the type
+    Wrap,
+
+    /// Saturate at the minimum or maximum value of the type
+    Saturate,
+
+    /// Panic on overflow
+    Panic,
+
+    /// Discard the result, i.e. the value of the
+    /// corresponding output becomes `None`
+    Discard,
+}
+
+/// A binary integer operation
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum IntegerOperation {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl IntegerOperation {
+    /// Apply the operation to both operands
+    ///
+    /// Returns `None` if the result overflows and the overflow
+    /// policy is `OverflowPolicy::Discard`.
+    pub fn apply<D>(self, lhs: &D, rhs: &D, overflow_policy: OverflowPolicy) -> Option<D>
+    where
+        D: OverflowArithmetic,
+    {
+        match overflow_policy {
+            OverflowPolicy::Wrap => Some(match self {
+                Self::Add => lhs.wrapping_add(rhs),
+                Self::Sub => lhs.wrapping_sub(rhs),
+                Self::Mul => lhs.wrapping_mul(rhs),
+            }),
+            OverflowPolicy::Saturate => Some(match self {
+                Self::Add => lhs.saturating_add(rhs),
+                Self::Sub => lhs.saturating_sub(rhs),
+                Self::Mul => lhs.saturating_mul(rhs),
+            }),
+            OverflowPolicy::Panic => {
+                let result = self.checked_apply(lhs, rhs);
+                assert!(result.is_some(), "arithmetic overflow in {:?}", self);
+                result
+            }
+            OverflowPolicy::Discard => self.checked_apply(lhs, rhs),
+        }
+    }
+
+    fn checked_apply<D>(self, lhs: &D, rhs: &D) -> Option<D>
+    where
+        D: OverflowArithmetic,
+    {
+        match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Sub => lhs.checked_sub(rhs),
+            Self::Mul => lhs.checked_mul(rhs),
+        }
+    }
+}
+
+/// An arithmetic node for integer values with two inputs and
+/// a configurable list of outputs
+///
+/// The integer counterpart of `CalcNode`. Each output is calculated
+/// by applying an operation to the values of both inputs. Overflows
+/// are handled according to the configured policy.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IntegerCalcNode<C, D> {
+    inputs: [Port<D, C>; 2],
+    outputs: VecPortBay<C, D>,
+    operations: Vec<IntegerOperation>,
+    overflow_policy: OverflowPolicy,
+}
+
+impl<C, D> IntegerCalcNode<C, D> {
+    pub const fn input_index_lhs() -> PortIndex {
+        PortIndex::new(0)
+    }
+
+    pub const fn input_index_rhs() -> PortIndex {
+        PortIndex::new(1)
+    }
+
+    /// Create a new node with one output per operation
+    pub fn new(operations: Vec<IntegerOperation>, overflow_policy: OverflowPolicy) -> Self {
+        Self {
+            inputs: [Port::new(), Port::new()],
+            outputs: VecPortBay::new(operations.len()),
+            operations,
+            overflow_policy,
+        }
+    }
+
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    pub fn set_overflow_policy(&mut self, overflow_policy: OverflowPolicy) {
+        self.overflow_policy = overflow_policy;
+    }
+
+    pub fn input(&self, input_index: PortIndex) -> &Port<D, C> {
+        &self.inputs[usize::from(input_index)]
+    }
+
+    pub fn input_mut(&mut self, input_index: PortIndex) -> &mut Port<D, C> {
+        &mut self.inputs[usize::from(input_index)]
+    }
+
+    pub fn output(&self, output_index: PortIndex) -> &Port<C, D> {
+        self.outputs.port(output_index)
+    }
+
+    pub fn output_mut(&mut self, output_index: PortIndex) -> &mut Port<C, D> {
+        self.outputs.port_mut(output_index)
+    }
+}
+
+impl<C, D> Node<C, D> for IntegerCalcNode<C, D>
+where
+    C: Clone + JoinablePortControl + 'static,
+    D: OverflowArithmetic + 'static,
+{
+}
+
+impl<C, D> NodeInputs<C, D> for IntegerCalcNode<C, D>
+where
+    C: Clone + JoinablePortControl,
+    D: OverflowArithmetic,
+{
+    fn num_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    fn accept_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+        packet: Packet<D, C>,
+    ) {
+        self.input_mut(input_index).accept_packet(packet);
+    }
+
+    fn try_dispatch_input_packet(
+        &mut self,
+        _token: AccessToken,
+        input_index: PortIndex,
+    ) -> Option<Packet<C, D>> {
+        self.input_mut(input_index).try_dispatch_packet()
+    }
+
+    fn with_input_port_mut(
+        &mut self,
+        input_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<D, C>),
+    ) -> bool {
+        self.inputs.get_mut(input_index.index()).map(f).is_some()
+    }
+}
+
+impl<C, D> NodeOutputs<C, D> for IntegerCalcNode<C, D>
+where
+    C: Clone + JoinablePortControl,
+    D: OverflowArithmetic,
+{
+    fn num_outputs(&self) -> usize {
+        self.outputs.num_ports()
+    }
+
+    fn accept_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+        packet: Packet<C, D>,
+    ) {
+        self.outputs.accept_packet(output_index, packet)
+    }
+
+    fn try_dispatch_output_packet(
+        &mut self,
+        _token: AccessToken,
+        output_index: PortIndex,
+    ) -> Option<Packet<D, C>> {
+        self.outputs.try_dispatch_packet(output_index)
+    }
+
+    fn with_output_port_mut(
+        &mut self,
+        output_index: PortIndex,
+        f: &mut dyn FnMut(&mut Port<C, D>),
+    ) -> bool {
+        self.outputs.get_port_mut(output_index).map(f).is_some()
+    }
+}
+
+impl<C, D> NodeProcessor for IntegerCalcNode<C, D>
+where
+    C: Clone + JoinablePortControl,
+    D: OverflowArithmetic,
+{
+    fn process_inputs(&mut self, _: AccessToken) {
+        let lhs = self.input_mut(Self::input_index_lhs()).incoming.take();
+        let rhs = self.input_mut(Self::input_index_rhs()).incoming.take();
+        let overflow_policy = self.overflow_policy;
+        for (output_port, operation) in self.outputs.ports_mut().zip(self.operations.iter()) {
+            if output_port.incoming.is_none() {
+                output_port.outgoing = None;
+                continue;
+            }
+            output_port.outgoing = match (&lhs, &rhs) {
+                (Some(lhs), Some(rhs)) => operation.apply(lhs, rhs, overflow_policy),
+                _ => None,
+            };
+        }
+    }
+
+    fn process_outputs(&mut self, _: AccessToken) {
+        // Both inputs are needed for every active output
+        let ctrl = self.outputs.ports().fold(None, |ctrl, port| {
+            if let Some(ctrl) = ctrl {
+                Some(C::join_next_port_control(ctrl, port.incoming.as_ref()))
+            } else {
+                port.incoming.clone()
+            }
+        });
+        for input_port in self.inputs.iter_mut() {
+            input_port.outgoing = ctrl.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::flow::Flow;
+
+    #[test]
+    fn apply_operations_with_overflow_policies() {
+        use IntegerOperation::*;
+        assert_eq!(Some(3u8), Add.apply(&1, &2, OverflowPolicy::Discard));
+        assert_eq!(Some(1u8), Add.apply(&255, &2, OverflowPolicy::Wrap));
+        assert_eq!(Some(255u8), Add.apply(&255, &2, OverflowPolicy::Saturate));
+        assert_eq!(None, Add.apply(&255u8, &2, OverflowPolicy::Discard));
+        assert_eq!(Some(255u8), Sub.apply(&1, &2, OverflowPolicy::Wrap));
+        assert_eq!(Some(0u8), Sub.apply(&1, &2, OverflowPolicy::Saturate));
+        assert_eq!(None, Sub.apply(&1u8, &2, OverflowPolicy::Discard));
+        assert_eq!(Some(-128i8), Mul.apply(&64, &2, OverflowPolicy::Wrap));
+        assert_eq!(Some(-128i8), Mul.apply(&64, &-2, OverflowPolicy::Saturate));
+        assert_eq!(Some(127i8), Mul.apply(&-64, &-2, OverflowPolicy::Saturate));
+        assert_eq!(None, Mul.apply(&64i8, &2, OverflowPolicy::Discard));
+    }
+
+    #[test]
+    fn apply_signed_operations_at_boundaries() {
+        use IntegerOperation::*;
+        for policy in &[
+            OverflowPolicy::Wrap,
+            OverflowPolicy::Saturate,
+            OverflowPolicy::Discard,
+            OverflowPolicy::Panic,
+        ] {
+            assert_eq!(Some(i8::MAX), Add.apply(&126, &1, *policy));
+            assert_eq!(Some(i8::MIN), Add.apply(&-127, &-1, *policy));
+            assert_eq!(Some(i8::MIN), Sub.apply(&-127, &1, *policy));
+            assert_eq!(Some(i8::MAX), Sub.apply(&-1, &i8::MIN, *policy));
+        }
+        assert_eq!(Some(i8::MIN), Add.apply(&i8::MAX, &1, OverflowPolicy::Wrap));
+        assert_eq!(
+            Some(i8::MAX),
+            Add.apply(&i8::MIN, &-1, OverflowPolicy::Wrap)
+        );
+        assert_eq!(Some(i8::MAX), Sub.apply(&i8::MIN, &1, OverflowPolicy::Wrap));
+        assert_eq!(Some(i8::MIN), Sub.apply(&0, &i8::MIN, OverflowPolicy::Wrap));
+        assert_eq!(
+            Some(i8::MAX),
+            Add.apply(&i8::MAX, &1, OverflowPolicy::Saturate)
+        );
+        assert_eq!(
+            Some(i8::MIN),
+            Add.apply(&i8::MIN, &-1, OverflowPolicy::Saturate)
+        );
+        assert_eq!(
+            Some(i8::MIN),
+            Sub.apply(&i8::MIN, &1, OverflowPolicy::Saturate)
+        );
+        assert_eq!(
+            Some(i8::MAX),
+            Sub.apply(&0, &i8::MIN, OverflowPolicy::Saturate)
+        );
+        assert_eq!(None, Add.apply(&i8::MAX, &1, OverflowPolicy::Discard));
+        assert_eq!(None, Add.apply(&i8::MIN, &-1, OverflowPolicy::Discard));
+        assert_eq!(None, Sub.apply(&i8::MIN, &1, OverflowPolicy::Discard));
+        assert_eq!(None, Sub.apply(&0, &i8::MIN, OverflowPolicy::Discard));
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic overflow in Add")]
+    fn panic_on_overflow() {
+        IntegerOperation::Add.apply(&255u8, &1, OverflowPolicy::Panic);
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic overflow in Add")]
+    fn panic_on_signed_overflow_above_max() {
+        IntegerOperation::Add.apply(&i8::MAX, &1, OverflowPolicy::Panic);
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic overflow in Add")]
+    fn panic_on_signed_overflow_below_min() {
+        IntegerOperation::Add.apply(&i8::MIN, &-1, OverflowPolicy::Panic);
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic overflow in Sub")]
+    fn panic_on_signed_underflow() {
+        IntegerOperation::Sub.apply(&i8::MIN, &1, OverflowPolicy::Panic);
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic overflow in Sub")]
+    fn panic_on_signed_negation_of_min() {
+        IntegerOperation::Sub.apply(&0, &i8::MIN, OverflowPolicy::Panic);
+    }
+
+    #[test]
+    fn process_only_requested_outputs() {
+        let node = IntegerCalcNode::<(), u8>::new(
+            vec![IntegerOperation::Add, IntegerOperation::Sub],
+            OverflowPolicy::Discard,
+        );
+        let mut flow: Flow<IntegerCalcNode<(), u8>, (), u8> = Flow::new();
+        let node_id = flow.add_node(node);
+        flow.process_outputs(node_id);
+        assert_eq!(
+            None,
+            flow.node(node_id)
+                .input(IntegerCalcNode::<(), u8>::input_index_lhs())
+                .outgoing
+        );
+        let node = flow.node_mut(node_id);
+        node.output_mut(PortIndex::new(0)).incoming = Some(());
+        node.output_mut(PortIndex::new(1)).incoming = Some(());
+        flow.process_outputs(node_id);
+        let node = flow.node_mut(node_id);
+        assert_eq!(
+            Some(()),
+            node.input(IntegerCalcNode::<(), u8>::input_index_rhs())
+                .outgoing
+        );
+        node.input_mut(IntegerCalcNode::<(), u8>::input_index_lhs())
+            .incoming = Some(200);
+        node.input_mut(IntegerCalcNode::<(), u8>::input_index_rhs())
+            .incoming = Some(100);
+        flow.process_inputs(node_id);
+        let node = flow.node_mut(node_id);
+        // The overflowing sum is discarded
+        assert_eq!(None, node.output(PortIndex::new(0)).outgoing);
+        assert_eq!(Some(100), node.output(PortIndex::new(1)).outgoing);
+        node.set_overflow_policy(OverflowPolicy::Saturate);
+        node.output_mut(PortIndex::new(1)).incoming = None;
+        node.input_mut(IntegerCalcNode::<(), u8>::input_index_lhs())
+            .incoming = Some(200);
+        node.input_mut(IntegerCalcNode::<(), u8>::input_index_rhs())
+            .incoming = Some(100);
+        flow.process_inputs(node_id);
+        let node = flow.node(node_id);
+        assert_eq!(Some(255), node.output(PortIndex::new(0)).outgoing);
+        assert_eq!(None, node.output(PortIndex::new(1)).outgoing);
+    }
+}
@@ -1,4 +1,14 @@
-#[derive(Debug, Clone, Copy)]
+/// A packet that is passed between connected ports
+///
+/// The piggyback allows to avoid repeated allocations when passing
+/// data back and forth: The receiver of a packet stores the piggyback
+/// in its outgoing slot, fills it with the results and then sends
+/// it back as the payload of a complementary packet. The original
+/// payload is returned as the piggyback of this packet and could
+/// be reused by the original sender in turn.
+///
+/// See also: `Packet::with_reusable()`, `Port::accept_with_buffer()`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Packet<P, B> {
     /// The payload
     ///
@@ -21,6 +31,25 @@ pub struct Packet<P, B> {
     pub piggyback: Option<B>,
 }
 
+impl<P, B> Packet<P, B> {
+    /// Create a packet without a piggyback
+    pub const fn new(payload: P) -> Self {
+        Self {
+            payload,
+            piggyback: None,
+        }
+    }
+
+    /// Create a packet with a pre-allocated buffer that
+    /// is supposed to be reused by the receiver
+    pub const fn with_reusable(payload: P, buffer: B) -> Self {
+        Self {
+            payload,
+            piggyback: Some(buffer),
+        }
+    }
+}
+
 /// An input or output port of a processing node.
 ///
 /// Ports have two generic parameters for packet data:
@@ -34,7 +63,7 @@ pub struct Packet<P, B> {
 /// Output ports accept packets with an incoming control payload
 /// in backward direction and dispatch packets with an outgoing
 /// data payload in forward direction.
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Port<I, O> {
     /// A slot (= a buffer with capacity 1) for the payload of an
     /// acceptable (= incoming) package
@@ -63,6 +92,16 @@ impl<I, O> Port<I, O> {
         self.outgoing = outgoing;
     }
 
+    /// Accept an incoming payload together with a pre-allocated
+    /// buffer for the outgoing payload
+    ///
+    /// The buffer is stored in the outgoing slot and is supposed to
+    /// be modified in place before it is dispatched as the payload
+    /// of a complementary packet.
+    pub fn accept_with_buffer(&mut self, incoming: I, buffer: O) {
+        self.accept_packet(Packet::with_reusable(incoming, buffer));
+    }
+
     /// Try to dispatch a packet with an outgoing payload
     pub fn try_dispatch_packet(&mut self) -> Option<Packet<O, I>> {
         if let Some(outgoing) = self.outgoing.take() {
@@ -77,12 +116,42 @@ impl<I, O> Port<I, O> {
 }
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PortIndex(usize);
 
 impl PortIndex {
     pub const fn new(index: usize) -> Self {
         Self(index)
     }
+
+    /// The 0-based index of the port
+    pub const fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl PartialEq<usize> for PortIndex {
+    fn eq(&self, other: &usize) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<PortIndex> for usize {
+    fn eq(&self, other: &PortIndex) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialOrd<usize> for PortIndex {
+    fn partial_cmp(&self, other: &usize) -> Option<std::cmp::Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+
+impl PartialOrd<PortIndex> for usize {
+    fn partial_cmp(&self, other: &PortIndex) -> Option<std::cmp::Ordering> {
+        self.partial_cmp(&other.0)
+    }
 }
 
 impl From<PortIndex> for usize {
@@ -105,7 +174,7 @@ pub trait PortBay<I, O> {
     fn try_dispatch_packet(&mut self, port_index: PortIndex) -> Option<Packet<O, I>>;
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct VecPortBay<I, O> {
     ports: Vec<Port<I, O>>,
 }
@@ -119,6 +188,38 @@ impl<I, O> VecPortBay<I, O> {
         Self { ports }
     }
 
+    /// Change the number of ports
+    ///
+    /// Additional ports are appended with empty slots. Surplus
+    /// ports are removed from the end, discarding their contents.
+    ///
+    /// The bay doesn't know about the connections of its ports.
+    /// Connections of surplus ports within a flow graph would
+    /// become dangling and must be removed before. Nodes should
+    /// be resized with `Flow::set_node_arity()` that disconnects
+    /// surplus ports first.
+    pub fn resize_ports(&mut self, num_ports: usize) {
+        self.ports.resize_with(num_ports, Port::new);
+    }
+
+    /// Append a single port with empty slots
+    ///
+    /// Returns the index of the new port.
+    pub fn push_port(&mut self) -> PortIndex {
+        self.ports.push(Port::new());
+        PortIndex::new(self.ports.len() - 1)
+    }
+
+    /// Remove all ports from the end beyond the given number
+    /// of ports, discarding their contents
+    ///
+    /// Has no effect if the bay has fewer ports.
+    ///
+    /// See also: `resize_ports()`
+    pub fn truncate_ports(&mut self, num_ports: usize) {
+        self.ports.truncate(num_ports);
+    }
+
     pub fn ports(&self) -> impl Iterator<Item = &Port<I, O>> {
         self.ports.iter()
     }
@@ -127,6 +228,27 @@ impl<I, O> VecPortBay<I, O> {
         self.ports.iter_mut()
     }
 
+    /// Iterate over all ports with an incoming value
+    pub fn active_values(&self) -> impl Iterator<Item = (PortIndex, &I)> {
+        self.ports.iter().enumerate().filter_map(|(index, port)| {
+            port.incoming
+                .as_ref()
+                .map(|incoming| (PortIndex::new(index), incoming))
+        })
+    }
+
+    /// Iterate over all ports with an incoming value for modification
+    pub fn active_values_mut(&mut self) -> impl Iterator<Item = (PortIndex, &mut I)> {
+        self.ports
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, port)| {
+                port.incoming
+                    .as_mut()
+                    .map(|incoming| (PortIndex::new(index), incoming))
+            })
+    }
+
     pub fn port(&self, port_index: PortIndex) -> &Port<I, O> {
         let index = usize::from(port_index);
         debug_assert!(index < self.ports.len());
@@ -138,6 +260,11 @@ impl<I, O> VecPortBay<I, O> {
         debug_assert!(index < self.ports.len());
         &mut self.ports[index]
     }
+
+    /// Get a port for modification if it exists
+    pub fn get_port_mut(&mut self, port_index: PortIndex) -> Option<&mut Port<I, O>> {
+        self.ports.get_mut(port_index.index())
+    }
 }
 
 impl<I, O> PortBay<I, O> for VecPortBay<I, O> {
@@ -153,3 +280,91 @@ impl<I, O> PortBay<I, O> for VecPortBay<I, O> {
         self.port_mut(port_index).try_dispatch_packet()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_and_truncate_ports() {
+        let mut bay: VecPortBay<i32, char> = VecPortBay::new(1);
+        bay.port_mut(PortIndex::new(0)).incoming = Some(1);
+        assert_eq!(PortIndex::new(1), bay.push_port());
+        assert_eq!(PortIndex::new(2), bay.push_port());
+        assert_eq!(3, bay.num_ports());
+        // Existing ports are retained and new ports are empty
+        assert_eq!(Some(1), bay.port(PortIndex::new(0)).incoming);
+        assert_eq!(Port::new(), *bay.port(PortIndex::new(2)));
+        // New ports accept and dispatch packets
+        bay.accept_packet(PortIndex::new(2), Packet::with_reusable(3, 'c'));
+        assert_eq!(
+            Some(Packet::with_reusable('c', 3)),
+            bay.try_dispatch_packet(PortIndex::new(2))
+        );
+        bay.port_mut(PortIndex::new(1)).accept_with_buffer(2, 'b');
+        bay.port_mut(PortIndex::new(2)).incoming = Some(3);
+        bay.truncate_ports(2);
+        assert_eq!(2, bay.num_ports());
+        assert!(bay.get_port_mut(PortIndex::new(2)).is_none());
+        // The remaining ports keep their contents
+        assert_eq!(Some(1), bay.port(PortIndex::new(0)).incoming);
+        assert_eq!(
+            Port {
+                incoming: Some(2),
+                outgoing: Some('b'),
+            },
+            *bay.port(PortIndex::new(1))
+        );
+        // The contents of removed ports are discarded
+        assert_eq!(PortIndex::new(2), bay.push_port());
+        assert_eq!(None, bay.port(PortIndex::new(2)).incoming);
+        // No effect when truncating beyond the number of ports
+        bay.truncate_ports(5);
+        assert_eq!(3, bay.num_ports());
+        bay.truncate_ports(0);
+        assert_eq!(0, bay.num_ports());
+    }
+
+    #[test]
+    fn iterate_active_values() {
+        let mut bay: VecPortBay<i32, ()> = VecPortBay::new(4);
+        bay.port_mut(PortIndex::new(1)).incoming = Some(1);
+        bay.port_mut(PortIndex::new(3)).incoming = Some(3);
+        bay.port_mut(PortIndex::new(2)).outgoing = Some(());
+        assert_eq!(
+            vec![(PortIndex::new(1), &1), (PortIndex::new(3), &3)],
+            bay.active_values().collect::<Vec<_>>()
+        );
+        for (port_index, value) in bay.active_values_mut() {
+            *value += usize::from(port_index) as i32 * 10;
+        }
+        assert_eq!(Some(11), bay.port(PortIndex::new(1)).incoming);
+        assert_eq!(None, bay.port(PortIndex::new(2)).incoming);
+        assert_eq!(Some(33), bay.port(PortIndex::new(3)).incoming);
+        assert_eq!(0, VecPortBay::<i32, ()>::new(2).active_values().count());
+    }
+
+    #[test]
+    fn reuse_buffers_when_passing_packets_back_and_forth() {
+        let mut input: Port<Vec<i32>, ()> = Port::new();
+        let mut output: Port<(), Vec<i32>> = Port::new();
+        // Request values with a pre-allocated buffer
+        input.accept_with_buffer(Vec::with_capacity(4), ());
+        let buffer_ptr = input.incoming.as_ref().unwrap().as_ptr();
+        for value in 0..3 {
+            let request = input.try_dispatch_packet().unwrap();
+            assert!(request.piggyback.is_some());
+            output.accept_packet(request);
+            let buffer = output.outgoing.as_mut().unwrap();
+            buffer.clear();
+            buffer.push(value);
+            input.accept_packet(output.try_dispatch_packet().unwrap());
+            assert_eq!(Some(vec![value]), input.incoming);
+            // The buffer has not been reallocated
+            assert_eq!(buffer_ptr, input.incoming.as_ref().unwrap().as_ptr());
+            // The control is returned for the next request
+            assert_eq!(Some(()), input.outgoing);
+            assert_eq!(Port::new(), output);
+        }
+    }
+}
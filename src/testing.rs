@@ -0,0 +1,143 @@
+use crate::{flow::*, node::*};
+
+/// A node that has been processed during a phase
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct ProcessEvent {
+    /// The processed node
+    pub node_id: NodeId,
+
+    /// The phase during which the node has been processed
+    pub phase: Phase,
+}
+
+/// Execute both passes for all nodes and record the
+/// order in which the nodes have been processed
+///
+/// See also: `Flow::process_all()`
+pub fn record_process_all<N, S, P>(flow: &mut Flow<N, S, P>) -> Result<Vec<ProcessEvent>, Cycle>
+where
+    N: Node<S, P>,
+{
+    flow.start_recording_process_events();
+    let result = flow.process_all();
+    let process_events = flow.stop_recording_process_events();
+    result.map(|_| process_events)
+}
+
+/// Shared fixtures for unit tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::{flow::*, node::*, port::PortIndex};
+
+    use std::{cell::Cell, rc::Rc};
+
+    pub(crate) fn socket(node_id: NodeId, port_index: usize) -> Socket {
+        Socket {
+            node_id,
+            port_index: PortIndex::new(port_index),
+        }
+    }
+
+    /// Borrow the node of a `node_enum!` variant
+    ///
+    /// Panics if the node is wrapped into a different variant.
+    macro_rules! unwrap_node {
+        ($node:expr, $variant:path) => {
+            match $node {
+                $variant(node) => node,
+                _ => unreachable!(),
+            }
+        };
+    }
+
+    pub(crate) use unwrap_node;
+
+    /// The most recent values of a sink input, oldest first
+    pub(crate) fn recent_values<C, D>(sink: &RingBufferSink<C, D>, input_index: usize) -> Vec<D>
+    where
+        D: Copy,
+    {
+        sink.recent(PortIndex::new(input_index))
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    pub(crate) type BoxedGenerate = Box<dyn FnMut() -> i32>;
+
+    pub(crate) type BoxedCompute = Box<dyn FnMut(&[Option<i32>], &mut [Option<i32>])>;
+
+    crate::node_enum! {
+        pub(crate) enum AdderTestNode: Node<(), i32> {
+            Source(FnSource<(), i32, BoxedGenerate>),
+            Adder(FnNode<(), i32, BoxedCompute>),
+            Sink(RingBufferSink<(), i32>),
+        }
+    }
+
+    pub(crate) type AdderFlow = Flow<AdderTestNode, (), i32>;
+
+    /// A source that generates an arithmetic sequence
+    pub(crate) fn counter_source(start: i32, step: i32) -> FnSource<(), i32, BoxedGenerate> {
+        let mut next = start;
+        FnSource::new(Box::new(move || {
+            let value = next;
+            next += step;
+            value
+        }))
+    }
+
+    /// Two sources that are summed up by an adder, followed by a sink
+    ///
+    /// The adder sums up all inputs that received a value. Returns the
+    /// flow graph, the ids of both sources, the adder and the sink, and
+    /// the number of invocations of the adder.
+    pub(crate) fn adder_flow(
+        lhs: FnSource<(), i32, BoxedGenerate>,
+        rhs: FnSource<(), i32, BoxedGenerate>,
+    ) -> (AdderFlow, [NodeId; 4], Rc<Cell<usize>>) {
+        let invocations = Rc::new(Cell::new(0));
+        let adder_invocations = Rc::clone(&invocations);
+        let add: BoxedCompute = Box::new(move |inputs, outputs| {
+            adder_invocations.set(adder_invocations.get() + 1);
+            outputs[0] = Some(inputs.iter().flatten().sum());
+        });
+        let mut flow = Flow::new();
+        let lhs_id = flow.add_node(lhs.into());
+        let rhs_id = flow.add_node(rhs.into());
+        let adder_id = flow.add_node(FnNode::new(2, 1, add).into());
+        let sink_id = flow.add_node(RingBufferSink::with_capacity(1, 8).into());
+        flow.connect(socket(lhs_id, 0), socket(adder_id, 0));
+        flow.connect(socket(rhs_id, 0), socket(adder_id, 1));
+        flow.connect(socket(adder_id, 0), socket(sink_id, 0));
+        (flow, [lhs_id, rhs_id, adder_id, sink_id], invocations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fixtures::socket, *};
+
+    #[test]
+    fn record_process_events_of_chain() {
+        let mut flow: Flow<OneToManySplitter<(), i32>, (), i32> = Flow::new();
+        // Added in reverse order to distinguish ids from the order
+        let c = flow.add_node(OneToManySplitter::new(1));
+        let b = flow.add_node(OneToManySplitter::new(1));
+        let a = flow.add_node(OneToManySplitter::new(1));
+        flow.connect(socket(a, 0), socket(b, 0));
+        flow.connect(socket(b, 0), socket(c, 0));
+        let event = |node_id, phase| ProcessEvent { node_id, phase };
+        assert_eq!(
+            vec![
+                event(c, Phase::Backward),
+                event(b, Phase::Backward),
+                event(a, Phase::Backward),
+                event(a, Phase::Forward),
+                event(b, Phase::Forward),
+                event(c, Phase::Forward),
+            ],
+            record_process_all(&mut flow).unwrap()
+        );
+    }
+}